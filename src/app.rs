@@ -1,16 +1,42 @@
-use std::{iter::Take, sync::Arc, vec::IntoIter};
+use std::{
+    collections::{HashMap, VecDeque},
+    iter::Take,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+    vec::IntoIter,
+};
 
 use crate::{
     components::{
+        backup_list::backup_list,
+        box_board::box_board,
         center_on_window::center,
         equation::{equation, CheckState, EqData},
-        menu::menu,
-        mult_table::Hidden,
+        input_modal::input_modal,
+        long_multiplication::long_multiplication,
+        menu::{menu, ExportScope},
+        mult_table::{Colouring, Hidden},
+        skip_count::skip_count,
+        word_problem::word_problem,
     },
     data::{
-        consts::{CELL_N, CELL_WIDTH, SPACING},
+        backup::{Backups, Snapshot},
+        bundle::{export_profiles, import_profile, Bundle, Resolution},
+        consts::{templates_dir, CELL_WIDTH, DEFAULT_SIZE, SET_SIZE, SPACING, WORD_PROBLEMS},
+        factors::Factors,
+        leitner::{box_counts, BOXES},
+        long_mult::LongMultiplication,
+        operation::{Operation, QuestionKind},
+        report::{export_report, ReportKind},
+        schedule::Strategy,
+        score::Attempt,
+        skip_count::SkipCount,
+        store::{run, FsStore, ProfileStore, Store},
+        time_limit::{countdown, TimeLimit},
         user::{ScoreWithEq, User},
         user_list::UserList,
+        word_problems::{Library, WordProblem},
     },
     helpers::{centered_text, convert_to_msg, extend_col, get_n},
 };
@@ -19,19 +45,72 @@ use iced::{
     alignment, executor, font,
     keyboard::{self, KeyCode},
     subscription::{self, Subscription},
-    widget::{button, column as col, container, focus_next, focus_previous, row, text, Column},
+    time,
+    widget::{
+        button, column as col, container, focus_next, focus_previous, row, text, Column, Row,
+    },
     Application, Command, Event, Length, Theme,
 };
 use rand::{seq::SliceRandom, thread_rng};
 
 pub struct MultiplicationTableApp {
+    store: Store,
     user: Option<Arc<User>>,
     user_list: Option<Arc<UserList>>,
-    equations: [EqData; CELL_N],
+    equations: Vec<EqData>,
     state: State,
     show_table: Hidden,
     show_results: bool,
     error: Option<Arc<Error>>,
+    /// bundled profiles that still wait to be imported
+    import_queue: VecDeque<User>,
+    /// profile whose name is already taken, waiting for the user to resolve it
+    import_conflict: Option<User>,
+    import_name: String,
+    backups: Backups,
+    /// snapshots listed in the "Restore from backup" dialog, `None` while it's closed
+    snapshots: Option<Vec<Snapshot>>,
+    /// range practiced instead of the profile's one until the app is closed
+    session_factors: Option<Factors>,
+    /// operation practiced in this session and shown in the table
+    operation: Operation,
+    /// number left blank in the questions, `None` picks one at random for each of them
+    question_kind: Option<QuestionKind>,
+    /// answers are picked from a few buttons instead of typed
+    multiple_choice: bool,
+    /// written multiplication shown instead of the equations while it's open
+    long: Option<LongMultiplication>,
+    /// word problem templates of every language
+    templates: Library,
+    /// word problems shown instead of the equations while they're open
+    problems: Option<ProblemSet>,
+    /// row of the table counted with blanks, shown instead of the equations while it's open
+    skip: Option<SkipCountSet>,
+    /// how the facts of a test are picked
+    strategy: Strategy,
+    /// time given to every set of a test, `None` when tests aren't timed
+    time_limit: Option<TimeLimit>,
+    /// when the time of the set being answered runs out
+    deadline: Option<Instant>,
+    /// times each fact `(operation, a, b)` was asked since the app was opened
+    shown: HashMap<(Operation, u32, u32), u32>,
+    /// what the colours of the table show
+    colouring: Colouring,
+    /// facts in each Leitner box and unanswered ones, shown in a dialog while it's `Some`
+    boxes: Option<([usize; BOXES], usize)>,
+}
+
+struct ProblemSet {
+    language: String,
+    problems: Vec<(WordProblem, EqData)>,
+    checked: bool,
+}
+
+struct SkipCountSet {
+    data: SkipCount,
+    /// one per blank, in the order they're answered
+    answers: Vec<EqData>,
+    checked: bool,
 }
 
 enum State {
@@ -56,15 +135,60 @@ pub enum Message {
     Input(usize, Option<u32>),
     Focus(usize, bool),
     StartTest,
-    UserListLoaded(UserList),
+    UserListLoaded(UserList, Option<Arc<anyhow::Error>>),
     UserLoaded(Box<User>, bool),
     UserCreated(Box<User>),
     UserSelected(String),
-    UserFileRenamed(String),
+    UserRenamed(Box<User>, UserList),
     SetError(Option<Arc<anyhow::Error>>),
     CreateUser(String),
     RenameCurrent(String),
+    ArchiveUser(String),
+    RestoreUser(String),
+    DeleteUser(String),
+    UserListChanged(UserList),
     SyncUserList,
+    ExportProfiles(ExportScope, String),
+    ImportProfiles(String),
+    BundleRead(Vec<User>),
+    ImportNameInput(String),
+    ResolveImport,
+    SkipImport,
+    ProfileImported(UserList),
+    ImportFailed(Arc<anyhow::Error>),
+    ExportReport(ReportKind, String),
+    SetFactors(Factors),
+    SetSessionFactors(Option<Factors>),
+    SetOperation(Operation),
+    SetQuestionKind(Option<QuestionKind>),
+    SetMultipleChoice(bool),
+    SetCommutative(bool),
+    StartLong,
+    LongInput(usize, Option<u32>),
+    CheckLong,
+    CloseLong,
+    TemplatesLoaded(Library, Option<Arc<anyhow::Error>>),
+    StartWordProblems(String),
+    ProblemInput(usize, Option<u32>),
+    CheckProblems,
+    CloseProblems,
+    StartSkipCount,
+    SkipInput(usize, Option<u32>),
+    CheckSkipCount,
+    CloseSkipCount,
+    SetStrategy(Strategy),
+    SetTimeLimit(Option<TimeLimit>),
+    Tick(Instant),
+    SetColouring(Colouring),
+    OpenBoxes,
+    CloseBoxes,
+    SetFluencyThreshold(Duration),
+    BackupTaken(Option<Arc<anyhow::Error>>),
+    OpenBackups,
+    BackupsListed(Vec<Snapshot>),
+    CloseBackups,
+    RestoreBackup(PathBuf),
+    BackupRestored(UserList),
     CheckResults,
     ContinueTest,
     StartSet,
@@ -72,15 +196,59 @@ pub enum Message {
 
 type Msg = <MultiplicationTableApp as Application>::Message;
 
+pub struct Flags {
+    /// where profiles are loaded from and saved to
+    pub store: Store,
+    /// snapshots of the data directory, taken on startup and after each completed test
+    pub backups: Backups,
+}
+
+impl Default for Flags {
+    fn default() -> Self {
+        #[cfg(feature = "sqlite")]
+        match crate::data::store::SqliteStore::open_in(crate::data::consts::app_dir()) {
+            Ok(store) => {
+                return Self {
+                    store: Arc::new(store),
+                    backups: Backups::default(),
+                }
+            }
+            Err(e) => println!("couldn't open the profile database, using RON files: {e:#}"),
+        }
+        Self {
+            store: Arc::new(FsStore::default()),
+            backups: Backups::default(),
+        }
+    }
+}
+
 impl MultiplicationTableApp {
+    /// Range of the table that is shown and practiced
+    fn factors(&self) -> Option<Factors> {
+        self.session_factors
+            .clone()
+            .or_else(|| Some(self.user.as_ref()?.factors().clone()))
+    }
+
     fn init_test(&mut self, size: usize) {
         // SAFETY - if we init the test, the user must be loaded
-        let mut all_scores: Vec<_> = self.user.as_mut().unwrap().iter().collect();
-        all_scores.shuffle(&mut thread_rng());
-        all_scores.sort_unstable();
-        let remaining = all_scores.into_iter().take(size);
+        let factors = self.factors().unwrap();
+        let user = Arc::make_mut(self.user.as_mut().unwrap());
+        user.grow(factors.max());
+        let all_scores: Vec<_> = user.iter_in(self.operation, &factors).collect();
+        let (operation, shown) = (self.operation, &self.shown);
+        let ordered = self.strategy.order(
+            all_scores,
+            |(a, b)| {
+                let fact = (operation, a as u32, b as u32);
+                shown.get(&fact).copied().unwrap_or(0)
+            },
+            &mut thread_rng(),
+        );
+        let remaining = ordered.into_iter().take(size);
         self.state = State::TestInProgress { remaining };
-        self.show_table = Hidden::Specified([[true; CELL_N]; CELL_N])
+        let n = factors.max();
+        self.show_table = Hidden::Specified(vec![vec![true; n]; n])
     }
 
     fn next_set(&mut self) -> Command<Msg> {
@@ -90,57 +258,252 @@ impl MultiplicationTableApp {
             if self.equations[0].correctness != CheckState::Unckecked {
                 // SAFETY - if we continue the test, it had to be initialized
                 {
-                    // SAFETY - NOONE should have access to the user but us at this time, so we can safely mutate
-                    let user_ref = Arc::<User>::get_mut(self.user.as_mut().unwrap()).unwrap();
-                    for e in self.equations {
+                    // a save still in flight may hold the old copy of the profile
+                    let user_ref = Arc::make_mut(self.user.as_mut().unwrap());
+                    for e in &self.equations {
                         let eq = e.get_numbers().unwrap();
                         if let Hidden::Specified(s) = &mut self.show_table {
                             s[eq.0 as usize - 1][eq.1 as usize - 1] = false;
+                            if user_ref.merges_mirrored(e.get_operation()) {
+                                s[eq.1 as usize - 1][eq.0 as usize - 1] = false;
+                            }
                         }
+                        let fluency = user_ref.fluency_threshold();
                         // SAFETY - as before
                         let s = user_ref
-                            .get_mut_score(eq.0 as usize - 1, eq.1 as usize - 1)
+                            .get_mut_score(e.get_operation(), eq.0 as usize - 1, eq.1 as usize - 1)
                             .unwrap();
-                        s.update(e.correctness == CheckState::Correct);
+                        s.record(e.attempt(), fluency);
                     }
                 }
                 if !self.show_results {
-                    command = Self::save_results(&self.user);
+                    // `remaining` still borrows the state, so the save is built from the fields
+                    // SAFETY - as before
+                    let user = self.user.as_ref().unwrap().clone();
+                    command = save_user(user, self.store.clone());
                 }
             }
             if remaining.len() != 0 {
-                let eq = get_n(remaining);
+                let eq = get_n(remaining, SET_SIZE, self.operation);
                 if let Some(e) = eq {
-                    self.equations = e;
+                    let (kind, multiple_choice) = (self.question_kind, self.multiple_choice);
+                    let mut rng = thread_rng();
+                    self.equations = e
+                        .into_iter()
+                        .map(|e| {
+                            let e = e.kind(pick_kind(kind));
+                            if multiple_choice {
+                                e.multiple_choice(&mut rng)
+                            } else {
+                                e
+                            }
+                        })
+                        .collect();
+                    for e in &self.equations {
+                        // SAFETY - equations of a test are built from a fact
+                        let (a, b) = e.get_numbers().unwrap();
+                        *self.shown.entry((e.get_operation(), a, b)).or_default() += 1;
+                    }
+                    let questions = self.equations.len();
+                    self.deadline = self
+                        .time_limit
+                        .map(|limit| Instant::now() + limit.for_set(questions));
                     return command;
                 }
             }
-            self.equations = [EqData::new(None); CELL_N];
+            // the snapshot has to include the results saved above
+            command = self.save_results_and_backup();
+            self.equations = vec![EqData::new(None); SET_SIZE];
             self.show_table = Hidden::None;
             self.state = State::NoTest;
+            self.deadline = None;
         }
         command
     }
 
+    /// Drops the test and exercises in progress without recording them
+    fn end_test(&mut self) {
+        self.state = State::NoTest;
+        self.deadline = None;
+        self.equations = vec![EqData::new(None); SET_SIZE];
+        self.show_table = Hidden::None;
+        self.show_results = false;
+        self.long = None;
+        self.problems = None;
+        self.skip = None;
+    }
+
+    /// Replaces the loaded profile. The test in progress was drawn from the old one,
+    /// its answers and table grid don't fit the new one
+    fn set_user(&mut self, user: User) {
+        self.end_test();
+        self.user = Some(Arc::new(user));
+    }
+
+    fn check_results(&mut self) -> Command<Msg> {
+        self.show_results = true;
+        self.deadline = None;
+        self.save_results()
+    }
+
+    /// Checks the set once its time runs out, the equations left unanswered count as wrong
+    fn tick(&mut self, now: Instant) -> Command<Msg> {
+        match self.deadline {
+            Some(deadline) if now >= deadline && self.state != State::NoTest => {
+                for e in &mut self.equations {
+                    e.time_out();
+                }
+                self.check_results()
+            }
+            _ => Command::none(),
+        }
+    }
+
     fn update_input(&mut self, index: usize, v: Option<u32>) -> Command<Msg> {
-        self.equations[index].correctness = if let Some(c) = v {
-            // SAFETY - if the value is in equations table, it 100% has the equation data
-            let (n1, n2) = self.equations[index].get_numbers().unwrap();
-            if n1 * n2 == c {
-                CheckState::Correct
-            } else {
-                CheckState::Wrong
+        self.equations[index].set_answer(v);
+        Command::none()
+    }
+
+    /// Checks the written multiplication and scores the table facts it was built from
+    fn check_long(&mut self) -> Command<Msg> {
+        let (Some(long), Some(user)) = (self.long.as_mut(), self.user.as_mut()) else {
+            return Command::none();
+        };
+        let judged = long.check();
+        if judged.is_empty() {
+            return Command::none();
+        }
+        let user = Arc::make_mut(user);
+        let fluency = user.fluency_threshold();
+        for (a, b, correct) in judged {
+            // digits may go past the profile's range
+            user.grow(a.max(b) as usize);
+            if let Some(s) =
+                user.get_mut_score(Operation::Multiplication, a as usize - 1, b as usize - 1)
+            {
+                s.record(Attempt::new(None, correct, None), fluency);
             }
-        } else {
-            CheckState::Unckecked
+        }
+        self.save_results()
+    }
+
+    /// Asks the weakest facts of the practiced operation as word problems in `language`
+    fn start_word_problems(&mut self, language: String) -> Command<Msg> {
+        if self.state != State::NoTest {
+            return Command::none();
+        }
+        let (Some(factors), Some(user)) = (self.factors(), self.user.as_mut()) else {
+            return Command::none();
+        };
+        let user = Arc::make_mut(user);
+        user.grow(factors.max());
+        let mut rng = thread_rng();
+        let mut weakest: Vec<_> = user.iter_in(self.operation, &factors).collect();
+        weakest.shuffle(&mut rng);
+        weakest.sort_unstable();
+        let facts: Vec<(u32, u32)> = weakest
+            .into_iter()
+            .take(WORD_PROBLEMS)
+            .map(Into::into)
+            .collect();
+        match self
+            .templates
+            .generate(&language, self.operation, &facts, &mut rng)
+        {
+            Ok(problems) => {
+                let problems = problems
+                    .into_iter()
+                    .map(|p| {
+                        let eq = EqData::new(Some(p.numbers())).operation(p.operation());
+                        (p, eq)
+                    })
+                    .collect();
+                self.long = None;
+                self.skip = None;
+                self.problems = Some(ProblemSet {
+                    language,
+                    problems,
+                    checked: false,
+                });
+            }
+            Err(e) => self.error = Some(Arc::new(e)),
+        }
+        Command::none()
+    }
+
+    /// Shows which word problems were solved and scores their facts
+    fn check_problems(&mut self) -> Command<Msg> {
+        let (Some(set), Some(user)) = (self.problems.as_mut(), self.user.as_mut()) else {
+            return Command::none();
+        };
+        if set.checked {
+            return Command::none();
+        }
+        set.checked = true;
+        record_answers(Arc::make_mut(user), set.problems.iter().map(|(_, e)| e));
+        self.save_results()
+    }
+
+    /// Counts the row of the weakest multiplication fact, leaving some of its numbers blank
+    fn start_skip_count(&mut self) -> Command<Msg> {
+        if self.state != State::NoTest {
+            return Command::none();
+        }
+        let (Some(factors), Some(user)) = (self.factors(), self.user.as_mut()) else {
+            return Command::none();
+        };
+        let user = Arc::make_mut(user);
+        user.grow(factors.max());
+        let mut rng = thread_rng();
+        let mut weakest: Vec<_> = user.iter_in(Operation::Multiplication, &factors).collect();
+        weakest.shuffle(&mut rng);
+        weakest.sort_unstable();
+        let Some((_, row)) = weakest.into_iter().next().map(<(u32, u32)>::from) else {
+            return Command::none();
+        };
+        let cols = factors.cols();
+        let data = SkipCount::new(row, *cols.start() as u32..=*cols.end() as u32, &mut rng);
+        let answers = data.facts().map(|f| EqData::new(Some(f))).collect();
+        self.long = None;
+        self.problems = None;
+        self.skip = Some(SkipCountSet {
+            data,
+            answers,
+            checked: false,
+        });
+        Command::none()
+    }
+
+    /// Shows which blanks of the counted row were right and scores their facts
+    fn check_skip_count(&mut self) -> Command<Msg> {
+        let (Some(set), Some(user)) = (self.skip.as_mut(), self.user.as_mut()) else {
+            return Command::none();
+        };
+        if set.checked {
+            return Command::none();
+        }
+        set.checked = true;
+        record_answers(Arc::make_mut(user), &set.answers);
+        self.save_results()
+    }
+
+    /// Counts the facts of the shown table in every Leitner box
+    fn open_boxes(&mut self) -> Command<Msg> {
+        let (Some(factors), Some(user)) = (self.factors(), self.user.as_mut()) else {
+            return Command::none();
         };
-        self.equations[index].value = v;
+        let user = Arc::make_mut(user);
+        user.grow(factors.max());
+        let scores: Vec<_> = user.iter_in(self.operation, &factors).collect();
+        let counts = box_counts(scores.iter().map(|s| s.score()));
+        let unanswered = scores.len() - counts.iter().sum::<usize>();
+        self.boxes = Some((counts, unanswered));
         Command::none()
     }
 
     fn update_focus(&mut self, index: usize, next: bool) -> Command<Msg> {
         if next {
-            if index == CELL_N - 1 {
+            if index + 1 == self.equations.len() {
                 Command::none()
             } else {
                 focus_next()
@@ -150,25 +513,53 @@ impl MultiplicationTableApp {
         }
     }
 
-    fn save_results(user: &Option<Arc<User>>) -> Command<Msg> {
+    fn save_results(&self) -> Command<Msg> {
         // SAFETY - if we want to save results, user HAS TO exist
-        let user = user.as_ref().unwrap().clone();
-        Command::perform(async move { user.update_file().await }, |r| {
-            Msg::SetError(r.err())
-        })
+        let user = self.user.as_ref().unwrap().clone();
+        save_user(user, self.store.clone())
+    }
+
+    fn save_results_and_backup(&self) -> Command<Msg> {
+        // SAFETY - if we want to save results, user HAS TO exist
+        let user = self.user.as_ref().unwrap().clone();
+        let store = self.store.clone();
+        let backups = self.backups.clone();
+        Command::perform(
+            async move {
+                user.update_file(store).await?;
+                take_backup(backups).await
+            },
+            |r| Msg::BackupTaken(r.err()),
+        )
     }
 
     fn sync_user_list(&self) -> Command<Msg> {
         let ul = self.user_list.as_ref().unwrap().clone();
-        Command::perform(async move { ul.save_to_file().await }, |r| {
+        let store = self.store.clone();
+        Command::perform(async move { ul.save_to_file(store).await }, |r| {
             Msg::SetError(r.err())
         })
     }
 
+    fn load_user(&self, name: String, should_sync: bool) -> Command<Msg> {
+        Command::perform(
+            User::load_user(self.store.clone(), name),
+            convert_to_msg(
+                move |u| Msg::UserLoaded(u, should_sync),
+                |e| Msg::SetError(Some(e)),
+            ),
+        )
+    }
+
     fn create_new_user(&mut self, user: String) -> Command<Msg> {
+        if let Some(Err(e)) = self.user_list.as_ref().map(|ul| ul.check_new_name(&user)) {
+            self.error = Some(Arc::new(e));
+            return Command::none();
+        }
+        let store = self.store.clone();
         // SAFETY - if we want to update the list, it's for sure loaded (it is loaded at the start of the program)
         Command::perform(
-            async move { User::create_new(&(user)).await },
+            async move { User::create_new(store, &(user)).await },
             convert_to_msg(
                 |u| Msg::UserCreated(Box::new(u)),
                 |e| Msg::SetError(Some(e)),
@@ -176,12 +567,132 @@ impl MultiplicationTableApp {
         )
     }
 
+    /// Applies `change` to a copy of the user list and, if it's valid, `store_op` to the profiles.
+    /// The list is only replaced once both succeed
+    fn change_user_list(
+        &mut self,
+        change: impl FnOnce(&mut UserList) -> Result<(), Error>,
+        store_op: impl FnOnce(&dyn ProfileStore) -> Result<(), Error> + Send + 'static,
+    ) -> Command<Msg> {
+        // SAFETY - the list is loaded at the start of the program
+        let mut list = UserList::clone(self.user_list.as_ref().unwrap());
+        if let Err(e) = change(&mut list) {
+            self.error = Some(Arc::new(e));
+            return Command::none();
+        }
+        let store = self.store.clone();
+        Command::perform(
+            async move { run(&store, store_op).await.map(|_| list) },
+            convert_to_msg(Msg::UserListChanged, |e| Msg::SetError(Some(Arc::new(e)))),
+        )
+    }
+
+    fn export_profiles(&mut self, scope: ExportScope, path: String) -> Command<Msg> {
+        // SAFETY - the menu only allows exporting once the list is loaded
+        let names = match scope {
+            ExportScope::Current => vec![self.user_list.as_ref().unwrap().get_current().to_owned()],
+            ExportScope::All => self.user_list.as_ref().unwrap().get_all().clone(),
+        };
+        let store = self.store.clone();
+        Command::perform(
+            async move {
+                run(&store, move |s| {
+                    export_profiles(s, &names, &PathBuf::from(path))
+                })
+                .await
+            },
+            |r| Msg::SetError(r.err().map(Arc::new)),
+        )
+    }
+
+    fn import_profiles(&mut self, path: String) -> Command<Msg> {
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || Bundle::read(&PathBuf::from(path))).await?
+            },
+            convert_to_msg(
+                |b: Bundle| Msg::BundleRead(b.into_profiles()),
+                |e| Msg::SetError(Some(Arc::new(e))),
+            ),
+        )
+    }
+
+    fn export_report(&self, kind: ReportKind, path: String) -> Command<Msg> {
+        let Some(user) = self.user.clone() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    export_report(&user, kind, &PathBuf::from(path))
+                })
+                .await?
+            },
+            |r| Msg::SetError(r.err().map(Arc::new)),
+        )
+    }
+
+    fn list_backups(&self) -> Command<Msg> {
+        let backups = self.backups.clone();
+        Command::perform(
+            async move { tokio::task::spawn_blocking(move || backups.list()).await? },
+            convert_to_msg(Msg::BackupsListed, |e| Msg::SetError(Some(Arc::new(e)))),
+        )
+    }
+
+    fn restore_backup(&mut self, snapshot: PathBuf) -> Command<Msg> {
+        self.snapshots = None;
+        let backups = self.backups.clone();
+        let store = self.store.clone();
+        Command::perform(
+            async move { run(&store, move |s| backups.restore(s, &snapshot)).await },
+            convert_to_msg(Msg::BackupRestored, |e| Msg::SetError(Some(Arc::new(e)))),
+        )
+    }
+
+    /// Imports the next queued profile, stopping to ask the user if its name is taken
+    fn import_next(&mut self) -> Command<Msg> {
+        let Some(user) = self.import_queue.pop_front() else {
+            // merged scores may belong to the current user, so it's reloaded with the list
+            let current = self.user_list.as_ref().unwrap().get_current().to_owned();
+            return self.load_user(current, true);
+        };
+        if self.user_list.as_ref().unwrap().contains(user.name()) {
+            self.import_name = user.name().to_owned();
+            self.import_conflict = Some(user);
+            Command::none()
+        } else {
+            self.import_resolved(user, None)
+        }
+    }
+
+    /// Imports `user`, `resolution` is `None` when its name isn't taken
+    fn import_resolved(&mut self, user: User, resolution: Option<Resolution>) -> Command<Msg> {
+        // SAFETY - the list is loaded at the start of the program
+        let mut list = UserList::clone(self.user_list.as_ref().unwrap());
+        let store = self.store.clone();
+        Command::perform(
+            async move {
+                run(&store, move |s| {
+                    import_profile(s, &mut list, user, resolution).map(|_| list)
+                })
+                .await
+            },
+            convert_to_msg(Msg::ProfileImported, |e| Msg::ImportFailed(Arc::new(e))),
+        )
+    }
+
     fn rename_current_user(&mut self, new_user_name: String) -> Command<Msg> {
         // SAFETY - if we want to update the list, it's for sure loaded (it is loaded at the start of the program)
         let current_user = self.user.as_ref().unwrap().clone();
+        let user_list = self.user_list.as_ref().unwrap().clone();
+        let store = self.store.clone();
         Command::perform(
-            async move { current_user.rename_user_file(new_user_name).await },
-            convert_to_msg(Msg::UserFileRenamed, |e| Msg::SetError(Some(e))),
+            async move { current_user.rename(store, &user_list, new_user_name).await },
+            convert_to_msg(
+                |(u, ul)| Msg::UserRenamed(u, ul),
+                |e| Msg::SetError(Some(e)),
+            ),
         )
     }
 }
@@ -189,19 +700,40 @@ impl MultiplicationTableApp {
 impl Application for MultiplicationTableApp {
     type Message = Message;
     type Theme = Theme;
-    type Flags = ();
+    type Flags = Flags;
     type Executor = executor::Default; //iced::futures::executor::ThreadPool;
 
-    fn new(_flags: Self::Flags) -> (Self, Command<Self::Message>) {
+    fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
+        let store = flags.store;
         (
             Self {
+                store: store.clone(),
                 user: None,
                 user_list: None,
-                equations: [EqData::new(None); CELL_N],
+                equations: vec![EqData::new(None); SET_SIZE],
                 state: State::NoTest,
                 show_table: Hidden::None,
                 show_results: false,
                 error: None,
+                import_queue: VecDeque::new(),
+                import_conflict: None,
+                import_name: String::new(),
+                backups: flags.backups.clone(),
+                snapshots: None,
+                session_factors: None,
+                operation: Operation::default(),
+                question_kind: Some(QuestionKind::Result),
+                multiple_choice: false,
+                long: None,
+                templates: Library::default(),
+                problems: None,
+                skip: None,
+                strategy: Strategy::default(),
+                time_limit: None,
+                deadline: None,
+                shown: HashMap::new(),
+                colouring: Colouring::default(),
+                boxes: None,
             },
             Command::batch(vec![
                 font::load(iced_aw::graphics::icons::ICON_FONT_BYTES).map(|r| {
@@ -210,7 +742,15 @@ impl Application for MultiplicationTableApp {
                             .map(|_| Arc::new(anyhow!("Icon font didn't load correctly"))),
                     )
                 }),
-                Command::perform(UserList::load_from_file(), Self::Message::UserListLoaded),
+                Command::perform(UserList::load_from_file(store), |(ul, e)| {
+                    Self::Message::UserListLoaded(ul, e)
+                }),
+                Command::perform(load_templates(), |(l, e)| {
+                    Self::Message::TemplatesLoaded(l, e)
+                }),
+                Command::perform(take_backup(flags.backups), |r| {
+                    Self::Message::BackupTaken(r.err())
+                }),
             ]),
         )
     }
@@ -230,31 +770,28 @@ impl Application for MultiplicationTableApp {
             Message::Focus(i, next) => self.update_focus(i, next),
             Message::ContinueTest => self.next_set(),
             Message::StartTest => {
-                self.init_test(CELL_N * CELL_N);
+                // SAFETY - the test can only be started once the user is loaded
+                let size = self.factors().unwrap().count();
+                self.init_test(size);
                 self.next_set()
             }
             Message::StartSet => {
-                self.init_test(CELL_N);
+                self.init_test(SET_SIZE);
                 self.next_set()
             }
-            Message::CheckResults => {
-                self.show_results = true;
-                Self::save_results(&self.user)
-            }
-            Message::UserListLoaded(ul) => {
+            Message::CheckResults => self.check_results(),
+            Message::Tick(now) => self.tick(now),
+            Message::UserListLoaded(ul, e) => {
                 self.user_list = Some(Arc::new(ul));
+                self.error = e;
                 let current = self.user_list.as_ref().unwrap().get_current();
-                Command::perform(User::load_user(current.to_owned()), |u| {
-                    Message::UserLoaded(u, false)
-                })
+                self.load_user(current.to_owned(), false)
             }
             Message::UserLoaded(u, should_sync) => {
                 if let Some(ref mut ul) = self.user_list {
-                    Arc::<UserList>::get_mut(ul)
-                        .unwrap()
-                        .switch_current(u.name().to_owned())
+                    Arc::make_mut(ul).switch_current(u.name().to_owned())
                 }
-                self.user = Some(Arc::new(*u));
+                self.set_user(*u);
                 if should_sync {
                     Command::perform(async {}, |_| Msg::SyncUserList)
                 } else {
@@ -267,7 +804,7 @@ impl Application for MultiplicationTableApp {
                     // SAFETY - NOONE should have access to the user but us at this time, so we can safely mutate
                     .unwrap()
                     .add_user(u.name());
-                self.user = Some(Arc::new(*u));
+                self.set_user(*u);
                 Command::perform(async {}, |_| Msg::SyncUserList)
             }
             Message::SetError(e) => {
@@ -275,25 +812,222 @@ impl Application for MultiplicationTableApp {
                 Command::none()
             }
             Message::CreateUser(u) => self.create_new_user(u),
-            Message::UserSelected(u) => {
-                Command::perform(User::load_user(u), |u| Message::UserLoaded(u, true))
-            }
+            Message::UserSelected(u) => self.load_user(u, true),
             Message::RenameCurrent(u) => self.rename_current_user(u),
+            Message::ArchiveUser(u) => {
+                let name = u.clone();
+                self.change_user_list(|ul| ul.archive_user(&u), move |s| s.archive_profile(&name))
+            }
+            Message::RestoreUser(u) => {
+                let name = u.clone();
+                self.change_user_list(|ul| ul.restore_user(&u), move |s| s.restore_profile(&name))
+            }
+            Message::DeleteUser(u) => {
+                let name = u.clone();
+                self.change_user_list(|ul| ul.delete_user(&u), move |s| s.delete_profile(&name))
+            }
+            Message::UserListChanged(ul) => {
+                let current = ul.get_current().to_owned();
+                self.user_list = Some(Arc::new(ul));
+                self.error = None;
+                if self.user.as_ref().map(|u| u.name()) != Some(current.as_str()) {
+                    // syncs the list once the new current user is loaded
+                    self.load_user(current, true)
+                } else {
+                    self.sync_user_list()
+                }
+            }
             Message::SyncUserList => self.sync_user_list(),
-            Message::UserFileRenamed(new_name) => {
-                let list = self.user_list.as_mut().unwrap();
-                Arc::<UserList>::get_mut(list)
-                    // SAFETY - NOONE should have access to the user but us at this time, so we can safely mutate
-                    .unwrap()
-                    .update_current_user_after_rename(new_name.clone());
-                Arc::<User>::get_mut(self.user.as_mut().unwrap())
-                    // SAFETY - NOONE should have access to the user but us at this time, so we can safely mutate
-                    .unwrap()
-                    .set_name(new_name);
-                let user = self.user.as_ref().unwrap().clone();
-                Command::perform(async move { user.update_file().await }, |_| {
-                    Msg::SyncUserList
-                })
+            Message::ExportProfiles(scope, path) => self.export_profiles(scope, path),
+            Message::ImportProfiles(path) => self.import_profiles(path),
+            Message::BundleRead(profiles) => {
+                self.import_queue = profiles.into();
+                self.error = None;
+                self.import_next()
+            }
+            Message::ImportNameInput(name) => {
+                self.import_name = name;
+                Command::none()
+            }
+            Message::ResolveImport => match self.import_conflict.take() {
+                Some(user) => {
+                    // keeping the taken name means adding the scores to that profile
+                    let resolution = if self.import_name == user.name() {
+                        Resolution::Merge
+                    } else {
+                        Resolution::Rename(std::mem::take(&mut self.import_name))
+                    };
+                    self.import_resolved(user, Some(resolution))
+                }
+                None => Command::none(),
+            },
+            Message::SkipImport => {
+                self.import_conflict = None;
+                self.import_next()
+            }
+            Message::ProfileImported(ul) => {
+                self.user_list = Some(Arc::new(ul));
+                self.import_next()
+            }
+            Message::ExportReport(kind, path) => self.export_report(kind, path),
+            Message::SetFactors(factors) => {
+                // the test in progress was drawn from the old range
+                if self.state != State::NoTest {
+                    return Command::none();
+                }
+                let Some(user) = self.user.as_mut() else {
+                    return Command::none();
+                };
+                Arc::make_mut(user).set_factors(factors);
+                self.session_factors = None;
+                self.save_results()
+            }
+            Message::SetSessionFactors(factors) => {
+                if self.state == State::NoTest {
+                    self.session_factors = factors;
+                }
+                Command::none()
+            }
+            Message::SetOperation(operation) => {
+                // equations of a running test keep the operation they were drawn with
+                if self.state == State::NoTest {
+                    self.operation = operation;
+                }
+                Command::none()
+            }
+            Message::SetQuestionKind(kind) => {
+                self.question_kind = kind;
+                Command::none()
+            }
+            Message::SetMultipleChoice(multiple_choice) => {
+                self.multiple_choice = multiple_choice;
+                Command::none()
+            }
+            Message::SetCommutative(commutative) => {
+                // the test in progress was drawn with the old setting
+                if self.state != State::NoTest {
+                    return Command::none();
+                }
+                let Some(user) = self.user.as_mut() else {
+                    return Command::none();
+                };
+                Arc::make_mut(user).set_commutative(commutative);
+                self.save_results()
+            }
+            Message::StartLong => {
+                if self.state == State::NoTest && self.user.is_some() {
+                    self.long = Some(LongMultiplication::random(&mut thread_rng()));
+                    self.problems = None;
+                    self.skip = None;
+                }
+                Command::none()
+            }
+            Message::LongInput(step, value) => {
+                if let Some(long) = self.long.as_mut() {
+                    long.set_answer(step, value);
+                }
+                Command::none()
+            }
+            Message::CheckLong => self.check_long(),
+            Message::CloseLong => {
+                self.long = None;
+                Command::none()
+            }
+            Message::TemplatesLoaded(library, e) => {
+                self.templates = library;
+                if e.is_some() {
+                    self.error = e;
+                }
+                Command::none()
+            }
+            Message::StartWordProblems(language) => self.start_word_problems(language),
+            Message::ProblemInput(i, v) => {
+                if let Some(set) = self.problems.as_mut() {
+                    set.problems[i].1.set_answer(v);
+                }
+                Command::none()
+            }
+            Message::CheckProblems => self.check_problems(),
+            Message::CloseProblems => {
+                self.problems = None;
+                Command::none()
+            }
+            Message::SetStrategy(strategy) => {
+                self.strategy = strategy;
+                Command::none()
+            }
+            Message::SetTimeLimit(limit) => {
+                // the set being answered keeps the time it was given
+                self.time_limit = limit;
+                Command::none()
+            }
+            Message::SetColouring(colouring) => {
+                self.colouring = colouring;
+                Command::none()
+            }
+            Message::OpenBoxes => self.open_boxes(),
+            Message::CloseBoxes => {
+                self.boxes = None;
+                Command::none()
+            }
+            Message::SetFluencyThreshold(threshold) => {
+                let Some(user) = self.user.as_mut() else {
+                    return Command::none();
+                };
+                Arc::make_mut(user).set_fluency_threshold(threshold);
+                // show what the new threshold changes
+                self.colouring = Colouring::Fluency;
+                self.save_results()
+            }
+            Message::StartSkipCount => self.start_skip_count(),
+            Message::SkipInput(i, v) => {
+                if let Some(set) = self.skip.as_mut() {
+                    // blanks are answered in order
+                    if set.answers[..i].iter().all(|e| e.value.is_some()) {
+                        set.answers[i].set_answer(v);
+                    }
+                }
+                Command::none()
+            }
+            Message::CheckSkipCount => self.check_skip_count(),
+            Message::CloseSkipCount => {
+                self.skip = None;
+                Command::none()
+            }
+            Message::BackupTaken(e) => {
+                if e.is_some() {
+                    self.error = e;
+                }
+                Command::none()
+            }
+            Message::OpenBackups => self.list_backups(),
+            Message::BackupsListed(snapshots) => {
+                self.snapshots = Some(snapshots);
+                Command::none()
+            }
+            Message::CloseBackups => {
+                self.snapshots = None;
+                Command::none()
+            }
+            Message::RestoreBackup(snapshot) => self.restore_backup(snapshot),
+            Message::BackupRestored(ul) => {
+                // scores of a test in progress would overwrite the restored ones
+                self.end_test();
+                self.error = None;
+                let current = ul.get_current().to_owned();
+                self.user_list = Some(Arc::new(ul));
+                self.load_user(current, false)
+            }
+            Message::ImportFailed(e) => {
+                self.import_queue.clear();
+                self.error = Some(e);
+                self.import_next()
+            }
+            Message::UserRenamed(u, ul) => {
+                self.set_user(*u);
+                self.user_list = Some(Arc::new(ul));
+                self.error = None;
+                Command::none()
             }
         }
     }
@@ -339,48 +1073,258 @@ impl Application for MultiplicationTableApp {
         .height(35)
         .spacing(10);
 
-        let table = center(
-            row![
-                extend_col(
-                    Column::new().push(table_title),
-                    self.equations.iter().enumerate().map(|(i, &e)| equation(
+        let operation_toggle = Operation::ALL
+            .into_iter()
+            .fold(Row::new(), |toggle, operation| {
+                toggle.push(button(operation.label(), {
+                    (self.state == State::NoTest && self.operation != operation)
+                        .then_some(Message::SetOperation(operation))
+                }))
+            })
+            .width(EQUATION_WIDTH * 2)
+            .height(35)
+            .spacing(10);
+
+        let exercise = if let Some(long) = &self.long {
+            col![
+                container(centered_text("Long multiplication")).center_x(),
+                long_multiplication(long.clone(), Self::Message::LongInput),
+                row![
+                    button("Check", Some(Message::CheckLong)),
+                    button("Next", long.is_solved().then_some(Message::StartLong)),
+                    button("Close", Some(Message::CloseLong)),
+                ]
+                .height(35)
+                .spacing(10),
+            ]
+            .width(Length::Shrink)
+            .spacing(SPACING)
+        } else if let Some(set) = &self.problems {
+            let has_all_answers = set
+                .problems
+                .iter()
+                .all(|(_, e)| e.correctness != CheckState::Unckecked);
+            extend_col(
+                Column::new().push(container(centered_text("Word problems")).center_x()),
+                set.problems.iter().enumerate().map(|(i, (p, e))| {
+                    word_problem(
+                        p.text(),
+                        *e,
+                        set.checked,
+                        move |v| Self::Message::ProblemInput(i, v),
+                        move || Self::Message::Focus(i, true),
+                    )
+                }),
+            )
+            .push(
+                row![
+                    button(
+                        "Check",
+                        (has_all_answers && !set.checked).then_some(Message::CheckProblems)
+                    ),
+                    button(
+                        "Next",
+                        set.checked
+                            .then(|| Message::StartWordProblems(set.language.clone()))
+                    ),
+                    button("Close", Some(Message::CloseProblems)),
+                ]
+                .height(35)
+                .spacing(10),
+            )
+            .width(EQUATION_WIDTH * 2)
+            .spacing(SPACING)
+        } else if let Some(set) = &self.skip {
+            let has_all_answers = set
+                .answers
+                .iter()
+                .all(|e| e.correctness != CheckState::Unckecked);
+            col![
+                container(centered_text("Skip counting")).center_x(),
+                skip_count(
+                    &set.data,
+                    &set.answers,
+                    set.checked,
+                    Self::Message::SkipInput,
+                    |i| Self::Message::Focus(i, true),
+                ),
+                row![
+                    button(
+                        "Check",
+                        (has_all_answers && !set.checked).then_some(Message::CheckSkipCount)
+                    ),
+                    button("Next", set.checked.then_some(Message::StartSkipCount)),
+                    button("Close", Some(Message::CloseSkipCount)),
+                ]
+                .width(EQUATION_WIDTH)
+                .height(35)
+                .spacing(10),
+            ]
+            .width(Length::Shrink)
+            .spacing(SPACING)
+        } else {
+            let mut column = Column::new();
+            if let Some(deadline) = self.deadline {
+                let left = deadline.saturating_duration_since(Instant::now());
+                column = column.push(
+                    container(centered_text(format!("Time left: {}", countdown(left))))
+                        .center_x()
+                        .width(EQUATION_WIDTH),
+                );
+            }
+            extend_col(
+                column.push(table_title),
+                self.equations.iter().enumerate().map(|(i, &e)| {
+                    equation(
                         e,
                         self.show_results,
                         move |is_correct| Self::Message::Input(i, is_correct),
-                        move || { Self::Message::Focus(i, true) }
-                    ))
-                )
-                .width(Length::Shrink)
-                .push(controls)
+                        move || Self::Message::Focus(i, true),
+                    )
+                }),
+            )
+            .width(Length::Shrink)
+            .push(controls)
+            .spacing(SPACING)
+        };
+
+        let table = center(
+            row![
+                exercise,
+                col![
+                    crate::components::mult_table::mult_table(
+                        &self.user,
+                        &self
+                            .factors()
+                            .unwrap_or_else(|| Factors::square(DEFAULT_SIZE)),
+                        self.operation,
+                        &self.show_table,
+                        &self.equations,
+                        self.colouring,
+                    ),
+                    operation_toggle,
+                ]
                 .spacing(SPACING),
-                crate::components::mult_table::mult_table(
-                    &self.user,
-                    &self.show_table,
-                    &self.equations
-                ),
             ]
             .spacing(30),
         );
         let menu = menu(self.user_list.as_ref().map(|ul| ul.get_all()))
+            .archived(self.user_list.as_ref().map(|ul| ul.get_archived()))
+            .languages(self.templates.languages())
+            .factors(
+                self.user.as_ref().map(|u| u.factors()),
+                self.session_factors.as_ref(),
+            )
             .on_create(Self::Message::CreateUser)
             .on_select(Self::Message::UserSelected)
-            .on_rename_current(Self::Message::RenameCurrent);
+            .on_rename_current(Self::Message::RenameCurrent)
+            .on_archive(Self::Message::ArchiveUser)
+            .on_restore(Self::Message::RestoreUser)
+            .on_delete(Self::Message::DeleteUser)
+            .on_export(Self::Message::ExportProfiles)
+            .on_import(Self::Message::ImportProfiles)
+            .on_report(Self::Message::ExportReport)
+            .on_backups(|| Self::Message::OpenBackups)
+            .on_factors(Self::Message::SetFactors)
+            .on_session_factors(Self::Message::SetSessionFactors)
+            .on_operation(Self::Message::SetOperation)
+            .on_question_kind(Self::Message::SetQuestionKind)
+            .on_multiple_choice(Self::Message::SetMultipleChoice)
+            .on_commutative(Self::Message::SetCommutative)
+            .on_long_multiplication(|| Self::Message::StartLong)
+            .on_word_problems(Self::Message::StartWordProblems)
+            .on_skip_count(|| Self::Message::StartSkipCount)
+            .on_strategy(Self::Message::SetStrategy)
+            .on_time_limit(Self::Message::SetTimeLimit)
+            .on_colouring(Self::Message::SetColouring)
+            .on_boxes(|| Self::Message::OpenBoxes)
+            .on_fluency_threshold(Self::Message::SetFluencyThreshold);
         let mut layout = col![menu, table];
         if let Some(err) = &self.error {
             layout = layout.push(container(text(err)).center_x().width(Length::Fill))
         }
-        layout.into()
+        let conflict = self.import_conflict.as_ref().map(|u| {
+            format!(
+                "{} already exists. Keep the name to add the imported scores to it, or enter a new one.",
+                u.name()
+            )
+        });
+        let layout = backup_list(
+            layout,
+            self.snapshots.as_deref(),
+            Self::Message::RestoreBackup,
+            Self::Message::CloseBackups,
+        );
+        let layout = box_board(
+            layout,
+            self.boxes.map(|(counts, _)| counts),
+            self.boxes.map_or(0, |(_, unanswered)| unanswered),
+            Self::Message::CloseBoxes,
+        );
+        input_modal(layout, self.import_name.clone(), Self::Message::SkipImport)
+            .maybe_title(conflict.is_some().then_some("Import user"))
+            .maybe_message(conflict)
+            .placeholder("user name")
+            .on_input(Self::Message::ImportNameInput)
+            .on_submit(Self::Message::ResolveImport)
+            .into()
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        subscription::events_with(|event, _| match event {
+        let keyboard = subscription::events_with(|event, _| match event {
             Event::Keyboard(keyboard::Event::KeyPressed {
                 key_code: KeyCode::Tab,
                 modifiers,
             }) => Some(Self::Message::Focus(0, !modifiers.shift())),
             _ => None,
-        })
+        });
+        // redraws the countdown, only while a timed set is being answered
+        let timer = match self.deadline {
+            Some(_) => time::every(TICK).map(Self::Message::Tick),
+            None => Subscription::none(),
+        };
+        Subscription::batch([keyboard, timer])
     }
 }
 
+fn pick_kind(kind: Option<QuestionKind>) -> QuestionKind {
+    kind.or_else(|| QuestionKind::ALL.choose(&mut thread_rng()).copied())
+        .unwrap_or_default()
+}
+
+fn save_user(user: Arc<User>, store: Store) -> Command<Msg> {
+    Command::perform(async move { user.update_file(store).await }, |r| {
+        Msg::SetError(r.err())
+    })
+}
+
+/// Records the answers given into the scores of the facts they were asked about
+fn record_answers<'e>(user: &mut User, answers: impl IntoIterator<Item = &'e EqData>) {
+    let fluency = user.fluency_threshold();
+    for e in answers {
+        // SAFETY - answers are only recorded for equations built from a fact
+        let (a, b) = e.get_numbers().unwrap();
+        if let Some(s) = user.get_mut_score(e.get_operation(), a as usize - 1, b as usize - 1) {
+            s.record(e.attempt(), fluency);
+        }
+    }
+}
+
+/// Reads the word problem templates off the async executor
+async fn load_templates() -> (Library, Option<Arc<Error>>) {
+    tokio::task::spawn_blocking(|| Library::load(&templates_dir()))
+        .await
+        .unwrap_or_else(|e| (Library::default(), Some(Arc::new(e.into()))))
+}
+
+/// Snapshots the data directory off the async executor
+async fn take_backup(backups: Backups) -> Result<(), Arc<Error>> {
+    tokio::task::spawn_blocking(move || backups.snapshot().map(|_| ()))
+        .await
+        .map_err(|e| Arc::new(Error::from(e)))?
+        .map_err(Arc::new)
+}
+
 const EQUATION_WIDTH: u16 = CELL_WIDTH * 3 + SPACING * 5 + 8;
+/// How often the countdown of a timed set is redrawn
+const TICK: Duration = Duration::from_millis(250);
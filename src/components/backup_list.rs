@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use iced::{
+    alignment::{self, Horizontal},
+    widget::{button, scrollable, Button, Column, Text},
+    Element, Length, Renderer,
+};
+use iced_aw::Card;
+
+use crate::data::backup::Snapshot;
+
+use super::center_on_window::center;
+
+/// "Restore from backup" dialog, shown over `underlay` while `snapshots` is `Some`
+pub fn backup_list<'a, Message: Clone + 'a>(
+    underlay: impl Into<Element<'a, Message, Renderer>>,
+    snapshots: Option<&'a [Snapshot]>,
+    on_restore: impl Fn(PathBuf) -> Message,
+    on_close: Message,
+) -> Element<'a, Message, Renderer> {
+    let overlay = snapshots.map(|snapshots| {
+        let list: Element<'a, Message, Renderer> = if snapshots.is_empty() {
+            Text::new("No backups were taken yet").into()
+        } else {
+            let rows = snapshots.iter().map(|s| {
+                let created = DateTime::<Local>::from(s.created()).format("%Y-%m-%d %H:%M:%S");
+                button(Text::new(format!("{created}\n{}", s.users().join(", "))))
+                    .width(Length::Fill)
+                    .on_press(on_restore(s.path().to_owned()))
+                    .into()
+            });
+            scrollable(Column::with_children(rows.collect()).spacing(5))
+                .height(Length::Fixed(300.0))
+                .into()
+        };
+        center(
+            Card::new(
+                Text::new("Restore from backup"),
+                Column::new()
+                    .push(Text::new(
+                        "Pick a snapshot to replace all users with. The current users are backed up first.",
+                    ))
+                    .push(list)
+                    .spacing(10),
+            )
+            .foot(
+                Button::new(Text::new("Cancel").horizontal_alignment(Horizontal::Center))
+                    .width(Length::Fill)
+                    .on_press(on_close.clone()),
+            )
+            .max_width(400.0)
+            .on_close(on_close.clone()),
+        )
+    });
+    iced_aw::modal(underlay, overlay)
+        .backdrop(on_close.clone())
+        .on_esc(on_close)
+        .align_y(alignment::Vertical::Top)
+        .into()
+}
@@ -0,0 +1,81 @@
+use iced::{
+    alignment::{self, Horizontal},
+    widget::{container, row, Button, Column, Text},
+    Element, Length, Renderer,
+};
+use iced_aw::Card;
+
+use crate::{
+    components::cell::text_cell,
+    data::leitner::{BOXES, REVIEW_DAYS},
+    styles::cell::CellColor,
+};
+
+use super::center_on_window::center;
+
+/// Number of facts in every Leitner box, shown over `underlay` while `counts` is `Some`.
+/// `unanswered` facts aren't in any box yet
+pub fn box_board<'a, Message: Clone + 'a>(
+    underlay: impl Into<Element<'a, Message, Renderer>>,
+    counts: Option<[usize; BOXES]>,
+    unanswered: usize,
+    on_close: Message,
+) -> Element<'a, Message, Renderer> {
+    let overlay = counts.map(|counts| {
+        let line = |cell: Element<'a, Message, Renderer>,
+                    facts: usize,
+                    note: String|
+         -> Element<'a, Message, Renderer> {
+            row![
+                cell,
+                container(Text::new(format!("{facts} facts"))).width(80),
+                Text::new(note),
+            ]
+            .spacing(10)
+            .align_items(alignment::Alignment::Center)
+            .into()
+        };
+        let boxes = counts
+            .iter()
+            .zip(REVIEW_DAYS)
+            .enumerate()
+            .map(|(i, (&facts, days))| {
+                let every = match days {
+                    1 => "asked every day".to_owned(),
+                    days => format!("asked every {days} days"),
+                };
+                line(
+                    text_cell(i + 1).color(CellColor::of_box(i + 1)).into(),
+                    facts,
+                    every,
+                )
+            });
+        let new = line(
+            text_cell("").into(),
+            unanswered,
+            "not answered yet".to_owned(),
+        );
+        center(
+            Card::new(
+                Text::new("Leitner boxes"),
+                Column::with_children(boxes.chain(std::iter::once(new)).collect())
+                    .push(Text::new(
+                        "A right answer moves a fact one box up, a wrong one back to box 1.",
+                    ))
+                    .spacing(10),
+            )
+            .foot(
+                Button::new(Text::new("Close").horizontal_alignment(Horizontal::Center))
+                    .width(Length::Fill)
+                    .on_press(on_close.clone()),
+            )
+            .max_width(400.0)
+            .on_close(on_close.clone()),
+        )
+    });
+    iced_aw::modal(underlay, overlay)
+        .backdrop(on_close.clone())
+        .on_esc(on_close)
+        .align_y(alignment::Vertical::Top)
+        .into()
+}
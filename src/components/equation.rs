@@ -1,11 +1,22 @@
+use std::time::{Duration, Instant};
+
 use iced::theme::TextInput;
-use iced::widget::{component, container, row, text_input, Component};
-use iced::{Element, Renderer};
+use iced::widget::{
+    button, column, component, container, row, text_input, Component, Row, TextInput as Input,
+};
+use iced::{Element, Length, Renderer};
+use rand::Rng;
 
 use crate::components::cell::text_cell;
+use crate::data::{
+    choices::{choices, CHOICES},
+    consts::CELL_WIDTH,
+    operation::{Operation, QuestionKind},
+    score::Attempt,
+};
 use crate::styles::{cell::CellColor, text_input::CustomTextStyles};
 
-use crate::helpers::centered_text;
+use crate::helpers::{centered_text, extend_row};
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum CheckState {
@@ -17,22 +28,104 @@ pub enum CheckState {
 #[derive(Clone, Copy, Debug)]
 pub struct EqData {
     numbers: Option<(u32, u32)>,
+    operation: Operation,
+    kind: QuestionKind,
+    /// answers offered as buttons instead of the text input
+    choices: Option<[u32; CHOICES]>,
     pub value: Option<u32>,
     pub correctness: CheckState,
+    shown_at: Option<Instant>,
+    answered_in: Option<Duration>,
+    /// left unanswered until the time of a timed set ran out
+    timed_out: bool,
 }
 
 impl EqData {
     pub fn new(numbers: Option<(u32, u32)>) -> Self {
         Self {
             numbers,
+            operation: Operation::default(),
+            kind: QuestionKind::default(),
+            choices: None,
             value: None,
             correctness: CheckState::Unckecked,
+            shown_at: numbers.map(|_| Instant::now()),
+            answered_in: None,
+            timed_out: false,
         }
     }
 
+    /// Asks the numbers with `operation` instead of multiplying them
+    pub fn operation(mut self, operation: Operation) -> Self {
+        self.operation = operation;
+        self
+    }
+
+    /// Leaves one of the numbers blank instead of the result
+    pub fn kind(mut self, kind: QuestionKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Offers the answer among a few wrong ones to pick from, call after setting the operation and kind
+    pub fn multiple_choice(mut self, rng: &mut impl Rng) -> Self {
+        self.choices = self
+            .numbers
+            .map(|(a, b)| choices(self.operation, self.kind, a, b, rng));
+        self
+    }
+
     pub fn get_numbers(&self) -> Option<(u32, u32)> {
         self.numbers
     }
+
+    pub fn get_operation(&self) -> Operation {
+        self.operation
+    }
+
+    pub fn get_kind(&self) -> QuestionKind {
+        self.kind
+    }
+
+    pub fn is_correct(&self, value: u32) -> bool {
+        self.numbers
+            .is_some_and(|(n1, n2)| self.kind.answer(self.operation, n1, n2) == value)
+    }
+
+    /// Remembers how long it took to give the current value
+    pub fn mark_answered(&mut self) {
+        self.answered_in = self.shown_at.map(|t| t.elapsed());
+    }
+
+    /// Takes the answer typed or picked, judging it right away
+    pub fn set_answer(&mut self, value: Option<u32>) {
+        self.correctness = match value {
+            Some(v) if self.is_correct(v) => CheckState::Correct,
+            Some(_) => CheckState::Wrong,
+            None => CheckState::Unckecked,
+        };
+        self.value = value;
+        self.mark_answered();
+    }
+
+    /// Counts the equation as wrong if it's still unanswered when the time runs out
+    pub fn time_out(&mut self) {
+        if self.numbers.is_some() && self.value.is_none() {
+            self.correctness = CheckState::Wrong;
+            self.timed_out = true;
+            self.mark_answered();
+        }
+    }
+
+    pub fn attempt(&self) -> Attempt {
+        Attempt::new(
+            self.value,
+            self.correctness == CheckState::Correct,
+            self.answered_in,
+        )
+        .kind(self.kind)
+        .timed_out(self.timed_out)
+    }
 }
 
 impl Default for EqData {
@@ -70,6 +163,7 @@ pub fn equation<Message>(
 #[derive(Debug, Clone)]
 pub enum Event {
     InputChanged(String),
+    Picked(u32),
     FocusNext,
 }
 
@@ -96,71 +190,110 @@ impl<Message> Component<Message, Renderer> for Equation<Message> {
     fn update(&mut self, _state: &mut Self::State, event: Event) -> Option<Message> {
         match event {
             Event::InputChanged(value) => {
-                let new_value = if value.is_empty() {
-                    None
-                } else {
-                    let v = value.parse().ok();
-                    if let Some(num) = &v {
-                        if num <= &999 {
-                            Some(*num)
-                        } else {
-                            self.eq_data.value
-                        }
-                    } else {
-                        self.eq_data.value
-                    }
-                };
+                let new_value = parse_answer(&value, self.eq_data.value);
                 if new_value != self.eq_data.value {
                     Some((self.on_change)(new_value))
                 } else {
                     None
                 }
             }
+            Event::Picked(value) => {
+                (Some(value) != self.eq_data.value).then(|| (self.on_change)(Some(value)))
+            }
             Event::FocusNext => Some((self.on_submit)()),
         }
     }
 
-    fn view(&self, _state: &Self::State) -> Element<Event, Renderer> {
+    fn view(&self, _state: &Self::State) -> Element<'_, Event, Renderer> {
         let numbers = self.eq_data.get_numbers();
-        let (text1, text2) = numbers
-            .map(|(n1, n2)| (n1.to_string(), n2.to_string()))
-            .unwrap_or(("".into(), "".into()));
-        let color = if self.show_checked {
+        let operation = self.eq_data.operation;
+        let (left, right, result) = numbers
+            .map(|(n1, n2)| {
+                let (left, right) = operation.operands(n1, n2);
+                let result = operation.answer(n1, n2);
+                (left.to_string(), right.to_string(), result.to_string())
+            })
+            .unwrap_or_default();
+        let check_state = if self.show_checked {
             &self.eq_data.correctness
         } else {
             &CheckState::Unckecked
-        }
-        .into();
-        let mut answer_input = text_input(
-            "",
-            self.eq_data
-                .value
-                .as_ref()
-                .map(u32::to_string)
-                .as_deref()
-                .unwrap_or(""),
-        )
-        .padding([5, 4])
-        .style(TextInput::Custom(Box::new(CustomTextStyles { color })))
-        .width(35)
-        .line_height(25.0 / 16.0);
-        if numbers.is_some() && !self.show_checked {
-            answer_input = answer_input
+        };
+        let can_answer = numbers.is_some() && !self.show_checked;
+        let mut input = answer_input(self.eq_data.value, check_state.into());
+        if can_answer {
+            input = input
                 .on_input(Event::InputChanged)
                 .on_submit(Event::FocusNext);
         }
 
-        container(
-            row![
-                text_cell(text1),
-                container(centered_text('x')).height(35).center_y(),
-                text_cell(text2),
-                container(centered_text('=')).height(35).center_y(),
-                answer_input,
-            ]
-            .spacing(10),
-        )
-        .into()
+        let answer: Element<_, _> = match self.eq_data.choices {
+            // the picked answer is shown in place of the input
+            Some(_) => text_cell(self.eq_data.value.map_or("?".into(), |v| v.to_string()))
+                .color(check_state.into())
+                .into(),
+            None => input.into(),
+        };
+        // the answer goes into the slot of the blank number
+        let (first, second, result): (Element<_, _>, Element<_, _>, Element<_, _>) =
+            match self.eq_data.kind {
+                QuestionKind::Result => (text_cell(left).into(), text_cell(right).into(), answer),
+                QuestionKind::MissingFirst => {
+                    (answer, text_cell(right).into(), text_cell(result).into())
+                }
+                QuestionKind::MissingSecond => {
+                    (text_cell(left).into(), answer, text_cell(result).into())
+                }
+            };
+
+        let equation = row![
+            first,
+            container(centered_text(operation.sign()))
+                .height(35)
+                .center_y(),
+            second,
+            container(centered_text('=')).height(35).center_y(),
+            result,
+        ]
+        .spacing(10);
+
+        match self.eq_data.choices {
+            Some(choices) => {
+                let buttons = choices.map(|c| {
+                    button(centered_text(c).width(Length::Fill))
+                        .width(Length::Fill)
+                        .on_press_maybe(can_answer.then_some(Event::Picked(c)))
+                });
+                column![equation, extend_row(Row::new(), buttons).spacing(5)]
+                    .spacing(5)
+                    .into()
+            }
+            None => container(equation).into(),
+        }
+    }
+}
+
+/// Cell-sized input of a number answer, coloured by how the answer was judged
+pub(crate) fn answer_input<'a, Message: Clone>(
+    value: Option<u32>,
+    color: CellColor,
+) -> Input<'a, Message, Renderer> {
+    let value = value.map(|v| v.to_string());
+    text_input("", value.as_deref().unwrap_or(""))
+        .padding([5, 4])
+        .style(TextInput::Custom(Box::new(CustomTextStyles { color })))
+        .width(CELL_WIDTH)
+        .line_height(25.0 / 16.0)
+}
+
+/// Value of the answer input after typing `value` into it, anything but a number up to 999 is ignored
+pub(crate) fn parse_answer(value: &str, current: Option<u32>) -> Option<u32> {
+    if value.is_empty() {
+        return None;
+    }
+    match value.parse() {
+        Ok(num) if num <= 999 => Some(num),
+        _ => current,
     }
 }
 
@@ -21,7 +21,9 @@ pub struct Modal<'a, Message> {
     ph: &'a str,
     underlay: Element<'a, Message, Renderer>,
     value: String,
-    error: Option<&'a str>,
+    message: Option<String>,
+    show_input: bool,
+    error: Option<String>,
     on_input: Option<Box<dyn Fn(String) -> Message>>,
     on_close: Message,
     on_submit: Option<Message>,
@@ -41,6 +43,8 @@ impl<'a, Message> Modal<'a, Message> {
             show_modal: false,
             on_input: None,
             on_submit: None,
+            message: None,
+            show_input: true,
             error: None,
             on_close,
             value,
@@ -63,6 +67,23 @@ impl<'a, Message> Modal<'a, Message> {
         self
     }
 
+    /// Shows the message above the text input
+    pub fn maybe_message(mut self, message: Option<String>) -> Self {
+        self.message = message;
+        self
+    }
+
+    /// Hiding the text input turns the modal into a confirmation dialog
+    pub fn show_input(mut self, show: bool) -> Self {
+        self.show_input = show;
+        self
+    }
+
+    pub fn maybe_error(mut self, error: Option<String>) -> Self {
+        self.error = error;
+        self
+    }
+
     pub fn placeholder(mut self, ph: &'a str) -> Self {
         self.ph = ph;
         self
@@ -77,19 +98,35 @@ impl<'a, Message> Modal<'a, Message> {
 impl<'u, Message: 'u + Clone> From<Modal<'u, Message>> for Element<'u, Message, Renderer> {
     fn from(modal: Modal<'u, Message>) -> Self {
         let overlay = if modal.show_modal {
-            let mut input = text_input(modal.ph, &modal.value);
-            if let Some(h) = modal.on_input {
-                input = input.on_input(h);
+            let mut input = iced::widget::Column::new();
+            if let Some(message) = modal.message {
+                input = input.push(
+                    container(Text::new(message))
+                        .width(Length::Fill)
+                        .padding([5, 0]),
+                );
             }
-            if let Some(h) = &modal.on_submit {
-                input = input.on_submit(h.clone());
+            if modal.show_input {
+                let mut text_input = text_input(modal.ph, &modal.value);
+                if let Some(h) = modal.on_input {
+                    text_input = text_input.on_input(h);
+                }
+                if let Some(h) = &modal.on_submit {
+                    text_input = text_input.on_submit(h.clone());
+                }
+                input = input.push(
+                    container(text_input)
+                        .center_y()
+                        .width(Length::Fill)
+                        .padding([5, 0]),
+                );
             }
-            let input = container(input)
-                .center_y()
-                .width(Length::Fill)
-                .padding([5, 0]);
             let content: Element<'u, Message, Renderer> = if let Some(err) = modal.error {
-                iced::widget::column![input, container(err).center_x().width(Length::Fill)].into()
+                iced::widget::column![
+                    input,
+                    container(Text::new(err)).center_x().width(Length::Fill)
+                ]
+                .into()
             } else {
                 input.into()
             };
@@ -0,0 +1,138 @@
+use iced::widget::{component, horizontal_rule, Column, Component, Row, Space};
+use iced::{Element, Renderer};
+
+use crate::components::{cell::text_cell, equation::answer_input};
+use crate::data::{
+    consts::CELL_WIDTH,
+    long_mult::{LongMultiplication, Slot},
+};
+use crate::styles::cell::CellColor;
+
+pub struct LongMultiplicationView<Message> {
+    data: LongMultiplication,
+    on_input: Box<dyn Fn(usize, Option<u32>) -> Message>,
+}
+
+/// Written multiplication laid out in columns, with an input for every digit and carry
+pub fn long_multiplication<Message>(
+    data: LongMultiplication,
+    on_input: impl Fn(usize, Option<u32>) -> Message + 'static,
+) -> LongMultiplicationView<Message> {
+    LongMultiplicationView {
+        data,
+        on_input: Box::new(on_input),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Input(usize, String),
+}
+
+impl<Message> LongMultiplicationView<Message> {
+    /// Puts `cells` at their columns of a line as wide as the whole layout, `sign` in front of them
+    fn line<'a>(
+        &self,
+        sign: Option<char>,
+        cells: impl IntoIterator<Item = (usize, Element<'a, Event, Renderer>)>,
+    ) -> Row<'a, Event, Renderer> {
+        let width = self.data.width();
+        let mut line: Vec<Option<Element<'a, Event, Renderer>>> =
+            (0..=width).map(|_| None).collect();
+        line[0] = sign.map(|s| text_cell(s).border_maybe(None).into());
+        for (column, cell) in cells {
+            line[width - column] = Some(cell);
+        }
+        Row::with_children(
+            line.into_iter()
+                .map(|cell| cell.unwrap_or_else(|| Space::with_width(CELL_WIDTH).into()))
+                .collect(),
+        )
+        .spacing(4)
+    }
+
+    fn number<'a>(&self, sign: Option<char>, n: u32) -> Row<'a, Event, Renderer> {
+        let digits = n.to_string().chars().rev().collect::<Vec<_>>();
+        self.line(
+            sign,
+            digits
+                .into_iter()
+                .enumerate()
+                .map(|(column, d)| (column, text_cell(d).border_maybe(None).into())),
+        )
+    }
+
+    /// Inputs of the steps on `row` holding `slot`
+    fn inputs<'a>(&self, row: usize, slot: Slot, sign: Option<char>) -> Row<'a, Event, Renderer> {
+        let cells = self
+            .data
+            .steps()
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.row() == row && s.slot() == slot)
+            .map(|(i, s)| {
+                let color = match self.data.checked(i) {
+                    Some(true) => CellColor::Green,
+                    Some(false) => CellColor::Red,
+                    None => CellColor::White,
+                };
+                let mut input =
+                    answer_input(self.data.answer(i), color).on_input(move |v| Event::Input(i, v));
+                if slot == Slot::Carry {
+                    input = input.size(12);
+                }
+                (s.column(), input.into())
+            });
+        self.line(sign, cells)
+    }
+}
+
+impl<Message> Component<Message, Renderer> for LongMultiplicationView<Message> {
+    type State = ();
+    type Event = Event;
+
+    fn update(&mut self, _state: &mut Self::State, event: Event) -> Option<Message> {
+        match event {
+            Event::Input(step, value) => {
+                // a cell holds a single digit, the last one typed replaces it
+                let new_value = value.chars().rev().find_map(|c| c.to_digit(10));
+                if value.is_empty() || new_value.is_some() {
+                    (new_value != self.data.answer(step)).then(|| (self.on_input)(step, new_value))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn view(&self, _state: &Self::State) -> Element<'_, Event, Renderer> {
+        let partial_rows = self.data.partial_rows();
+        let mut layout = Column::new()
+            .push(self.number(None, self.data.top()))
+            .push(self.number(Some('×'), self.data.bottom()))
+            .push(horizontal_rule(2))
+            .spacing(4);
+        for row in 0..partial_rows {
+            let sign = (partial_rows > 1 && row + 1 == partial_rows).then_some('+');
+            layout = layout
+                .push(self.inputs(row, Slot::Carry, None))
+                .push(self.inputs(row, Slot::Digit, sign));
+        }
+        if partial_rows > 1 {
+            layout =
+                layout
+                    .push(horizontal_rule(2))
+                    .push(self.inputs(partial_rows, Slot::Digit, None));
+        }
+        layout.into()
+    }
+}
+
+impl<'a, Message> From<LongMultiplicationView<Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(view: LongMultiplicationView<Message>) -> Self {
+        component(view)
+    }
+}
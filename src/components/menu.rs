@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use iced::{
     alignment,
     widget::{button, component, container, text, Component},
@@ -5,25 +7,114 @@ use iced::{
 };
 use iced_aw::{helpers::menu_tree, menu_tree, MenuBar};
 
-use crate::styles::menu_button::ButtonStyle;
+use crate::{
+    components::mult_table::Colouring,
+    data::{
+        bundle::default_bundle_path,
+        consts::DEFAULT_SIZE,
+        factors::Factors,
+        operation::{Operation, QuestionKind},
+        report::{default_report_path, ReportFormat, ReportKind},
+        schedule::Strategy,
+        time_limit::TimeLimit,
+        user_list::UserList,
+    },
+    styles::menu_button::ButtonStyle,
+};
 
 pub struct Menu<'u, Message> {
     user_list: Option<&'u Vec<String>>,
+    archived: Option<&'u Vec<String>>,
+    languages: Vec<&'u str>,
+    /// range of the profile and the one of the session, the range editors start from them
+    factors: Option<&'u Factors>,
+    session_factors: Option<&'u Factors>,
     on_create: Option<Box<dyn Fn(String) -> Message>>,
     on_select: Option<Box<dyn Fn(String) -> Message>>,
     on_rename_current: Option<Box<dyn Fn(String) -> Message>>,
+    on_archive: Option<Box<dyn Fn(String) -> Message>>,
+    on_restore: Option<Box<dyn Fn(String) -> Message>>,
+    on_delete: Option<Box<dyn Fn(String) -> Message>>,
+    on_export: Option<Box<dyn Fn(ExportScope, String) -> Message>>,
+    on_import: Option<Box<dyn Fn(String) -> Message>>,
+    on_report: Option<Box<dyn Fn(ReportKind, String) -> Message>>,
+    on_backups: Option<Box<dyn Fn() -> Message>>,
+    on_factors: Option<Box<dyn Fn(Factors) -> Message>>,
+    on_session_factors: Option<Box<dyn Fn(Option<Factors>) -> Message>>,
+    on_operation: Option<Box<dyn Fn(Operation) -> Message>>,
+    on_question_kind: Option<Box<dyn Fn(Option<QuestionKind>) -> Message>>,
+    on_multiple_choice: Option<Box<dyn Fn(bool) -> Message>>,
+    on_commutative: Option<Box<dyn Fn(bool) -> Message>>,
+    on_long_multiplication: Option<Box<dyn Fn() -> Message>>,
+    on_word_problems: Option<Box<dyn Fn(String) -> Message>>,
+    on_skip_count: Option<Box<dyn Fn() -> Message>>,
+    on_strategy: Option<Box<dyn Fn(Strategy) -> Message>>,
+    on_time_limit: Option<Box<dyn Fn(Option<TimeLimit>) -> Message>>,
+    on_colouring: Option<Box<dyn Fn(Colouring) -> Message>>,
+    on_boxes: Option<Box<dyn Fn() -> Message>>,
+    on_fluency_threshold: Option<Box<dyn Fn(Duration) -> Message>>,
+}
+
+/// Which profiles go into an exported bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportScope {
+    Current,
+    All,
 }
 
 impl<'u, Message> Menu<'u, Message> {
     pub fn new(user_list: Option<&'u Vec<String>>) -> Self {
         Self {
             user_list,
+            archived: None,
+            languages: vec![],
+            factors: None,
+            session_factors: None,
             on_create: None,
             on_select: None,
             on_rename_current: None,
+            on_archive: None,
+            on_restore: None,
+            on_delete: None,
+            on_export: None,
+            on_import: None,
+            on_report: None,
+            on_backups: None,
+            on_factors: None,
+            on_session_factors: None,
+            on_operation: None,
+            on_question_kind: None,
+            on_multiple_choice: None,
+            on_commutative: None,
+            on_long_multiplication: None,
+            on_word_problems: None,
+            on_skip_count: None,
+            on_strategy: None,
+            on_time_limit: None,
+            on_colouring: None,
+            on_boxes: None,
+            on_fluency_threshold: None,
         }
     }
 
+    pub fn archived(mut self, archived: Option<&'u Vec<String>>) -> Self {
+        self.archived = archived;
+        self
+    }
+
+    /// Languages word problems can be asked in
+    pub fn languages(mut self, languages: Vec<&'u str>) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    /// Ranges the range editors are filled with, the session one falls back to the profile's
+    pub fn factors(mut self, profile: Option<&'u Factors>, session: Option<&'u Factors>) -> Self {
+        self.factors = profile;
+        self.session_factors = session;
+        self
+    }
+
     pub fn on_create(mut self, handle: impl Fn(String) -> Message + 'static) -> Self {
         self.on_create = Some(Box::new(handle));
         self
@@ -38,6 +129,142 @@ impl<'u, Message> Menu<'u, Message> {
         self.on_rename_current = Some(Box::new(handle));
         self
     }
+
+    pub fn on_archive(mut self, handle: impl Fn(String) -> Message + 'static) -> Self {
+        self.on_archive = Some(Box::new(handle));
+        self
+    }
+
+    pub fn on_restore(mut self, handle: impl Fn(String) -> Message + 'static) -> Self {
+        self.on_restore = Some(Box::new(handle));
+        self
+    }
+
+    pub fn on_delete(mut self, handle: impl Fn(String) -> Message + 'static) -> Self {
+        self.on_delete = Some(Box::new(handle));
+        self
+    }
+
+    pub fn on_export(mut self, handle: impl Fn(ExportScope, String) -> Message + 'static) -> Self {
+        self.on_export = Some(Box::new(handle));
+        self
+    }
+
+    pub fn on_import(mut self, handle: impl Fn(String) -> Message + 'static) -> Self {
+        self.on_import = Some(Box::new(handle));
+        self
+    }
+
+    pub fn on_report(mut self, handle: impl Fn(ReportKind, String) -> Message + 'static) -> Self {
+        self.on_report = Some(Box::new(handle));
+        self
+    }
+
+    pub fn on_backups(mut self, handle: impl Fn() -> Message + 'static) -> Self {
+        self.on_backups = Some(Box::new(handle));
+        self
+    }
+
+    /// Called with the new range of the current profile
+    pub fn on_factors(mut self, handle: impl Fn(Factors) -> Message + 'static) -> Self {
+        self.on_factors = Some(Box::new(handle));
+        self
+    }
+
+    /// Called with the range practiced until the app is closed, `None` goes back to the profile's one
+    pub fn on_session_factors(
+        mut self,
+        handle: impl Fn(Option<Factors>) -> Message + 'static,
+    ) -> Self {
+        self.on_session_factors = Some(Box::new(handle));
+        self
+    }
+
+    /// Called with the operation practiced until another one is picked
+    pub fn on_operation(mut self, handle: impl Fn(Operation) -> Message + 'static) -> Self {
+        self.on_operation = Some(Box::new(handle));
+        self
+    }
+
+    /// Called with the kind of questions asked, `None` mixes all of them
+    pub fn on_question_kind(
+        mut self,
+        handle: impl Fn(Option<QuestionKind>) -> Message + 'static,
+    ) -> Self {
+        self.on_question_kind = Some(Box::new(handle));
+        self
+    }
+
+    /// Called with whether answers are picked from buttons instead of typed
+    pub fn on_multiple_choice(mut self, handle: impl Fn(bool) -> Message + 'static) -> Self {
+        self.on_multiple_choice = Some(Box::new(handle));
+        self
+    }
+
+    /// Called with whether the current profile counts 3×7 and 7×3 as one fact
+    pub fn on_commutative(mut self, handle: impl Fn(bool) -> Message + 'static) -> Self {
+        self.on_commutative = Some(Box::new(handle));
+        self
+    }
+
+    /// Called to open the written multiplication exercise
+    pub fn on_long_multiplication(mut self, handle: impl Fn() -> Message + 'static) -> Self {
+        self.on_long_multiplication = Some(Box::new(handle));
+        self
+    }
+
+    /// Called with the language of the word problems to open
+    pub fn on_word_problems(mut self, handle: impl Fn(String) -> Message + 'static) -> Self {
+        self.on_word_problems = Some(Box::new(handle));
+        self
+    }
+
+    /// Called to open the skip-counting exercise
+    pub fn on_skip_count(mut self, handle: impl Fn() -> Message + 'static) -> Self {
+        self.on_skip_count = Some(Box::new(handle));
+        self
+    }
+
+    /// Called with the way the facts of a test are picked
+    pub fn on_strategy(mut self, handle: impl Fn(Strategy) -> Message + 'static) -> Self {
+        self.on_strategy = Some(Box::new(handle));
+        self
+    }
+
+    /// Called with the time given to each set, `None` turns the timed mode off
+    pub fn on_time_limit(
+        mut self,
+        handle: impl Fn(Option<TimeLimit>) -> Message + 'static,
+    ) -> Self {
+        self.on_time_limit = Some(Box::new(handle));
+        self
+    }
+
+    /// Called with what the colours of the table should show
+    pub fn on_colouring(mut self, handle: impl Fn(Colouring) -> Message + 'static) -> Self {
+        self.on_colouring = Some(Box::new(handle));
+        self
+    }
+
+    /// Called to show how many facts are in each Leitner box
+    pub fn on_boxes(mut self, handle: impl Fn() -> Message + 'static) -> Self {
+        self.on_boxes = Some(Box::new(handle));
+        self
+    }
+
+    /// Called with the longest time a right answer may take to count as fluent
+    pub fn on_fluency_threshold(mut self, handle: impl Fn(Duration) -> Message + 'static) -> Self {
+        self.on_fluency_threshold = Some(Box::new(handle));
+        self
+    }
+
+    fn name_taken(&self, name: &str) -> bool {
+        self.user_list
+            .into_iter()
+            .chain(self.archived)
+            .flatten()
+            .any(|u| u == name)
+    }
 }
 
 pub fn menu<Message>(user_list: Option<&'_ Vec<String>>) -> Menu<'_, Message> {
@@ -49,22 +276,88 @@ pub enum Event<'u> {
     UserSelected(&'u str),
     OpenAddUserModal,
     OpenRenameModal,
+    OpenArchiveModal(&'u str),
+    OpenDeleteModal(&'u str),
+    RestoreUser(&'u str),
+    OpenExportModal(ExportScope),
+    OpenImportModal,
+    OpenReportModal(ReportKind),
+    OpenBackups,
+    SizeSelected(usize),
+    OpenRangeModal { session: bool },
+    ClearSessionRange,
+    OperationSelected(Operation),
+    QuestionKindSelected(Option<QuestionKind>),
+    MultipleChoiceSelected(bool),
+    CommutativeSelected(bool),
+    OpenLongMultiplication,
+    OpenWordProblems(&'u str),
+    OpenSkipCount,
+    StrategySelected(Strategy),
+    TimeLimitSelected(Option<TimeLimit>),
+    ColouringSelected(Colouring),
+    OpenBoxes,
+    FluencyThresholdSelected(Duration),
     ModalInput(String),
     ModalSubmit,
     CloseModal,
     Noop,
 }
 
+/// Action waiting for the user to confirm it in the modal
+enum Pending {
+    Archive(String),
+    Delete(String),
+    Export(ExportScope),
+    Import,
+    Report(ReportKind),
+    Range { session: bool },
+}
+
+impl Pending {
+    fn message(&self) -> String {
+        match self {
+            Pending::Archive(u) => format!(
+                "{u} will be hidden from the user list. The scores are kept and can be restored from \"Archived users\"."
+            ),
+            Pending::Delete(u) => format!("{u} and all their scores will be deleted for good."),
+            Pending::Export(_) => "File to save the profiles to:".into(),
+            Pending::Import => "File to import the profiles from:".into(),
+            Pending::Report(ReportKind { with_attempts: false, .. }) => "File to save the scores of the current user to:".into(),
+            Pending::Report(ReportKind { with_attempts: true, .. }) => "File to save the scores of the current user to. The answer history goes into a separate \"_attempts\" file next to it:".into(),
+            Pending::Range { session } => format!(
+                "First factors × second factors, like 6-9 x 1-12. {}",
+                if *session {
+                    "Used until the app is closed."
+                } else {
+                    "Saved in the profile."
+                }
+            ),
+        }
+    }
+
+    /// Whether the action takes a value from the text input
+    fn needs_input(&self) -> bool {
+        matches!(
+            self,
+            Pending::Export(_) | Pending::Import | Pending::Report(_) | Pending::Range { .. }
+        )
+    }
+}
+
 pub struct State {
     modal_title: Option<&'static str>,
     input_value: String,
     error: Option<String>,
+    pending: Option<Pending>,
 }
 
 impl State {
     fn close(&mut self) {
         self.modal_title = None;
         self.input_value = "".into();
+        self.error = None;
+        self.pending = None;
     }
 }
 
@@ -74,6 +367,7 @@ impl Default for State {
             modal_title: None,
             error: None,
             input_value: "".into(),
+            pending: None,
         }
     }
 }
@@ -94,16 +388,114 @@ impl<'u, Message> Component<Message, Renderer> for Menu<'u, Message> {
             }
             Event::OpenAddUserModal => state.modal_title = Some("Add new user"),
             Event::OpenRenameModal => state.modal_title = Some("Rename current user"),
+            Event::OpenArchiveModal(u) => {
+                state.modal_title = Some("Archive user");
+                state.pending = Some(Pending::Archive(u.to_owned()));
+            }
+            Event::OpenDeleteModal(u) => {
+                state.modal_title = Some("Delete user");
+                state.pending = Some(Pending::Delete(u.to_owned()));
+            }
+            Event::RestoreUser(u) => ev = self.on_restore.as_ref().map(|h| h(u.to_owned())),
+            Event::OpenExportModal(scope) => {
+                state.modal_title = Some("Export users");
+                state.input_value = default_bundle_path().display().to_string();
+                state.pending = Some(Pending::Export(scope));
+            }
+            Event::OpenImportModal => {
+                state.modal_title = Some("Import users");
+                state.input_value = default_bundle_path().display().to_string();
+                state.pending = Some(Pending::Import);
+            }
+            Event::OpenReportModal(kind) => {
+                state.modal_title = Some("Export scores");
+                state.input_value = default_report_path(kind.format).display().to_string();
+                state.pending = Some(Pending::Report(kind));
+            }
+            Event::OpenBackups => ev = self.on_backups.as_ref().map(|h| h()),
+            Event::SizeSelected(size) => {
+                ev = self.on_factors.as_ref().map(|h| h(Factors::square(size)))
+            }
+            Event::OpenRangeModal { session } => {
+                state.modal_title = Some(if session {
+                    "Range for this session"
+                } else {
+                    "Range of the profile"
+                });
+                let current = if session {
+                    self.session_factors.or(self.factors)
+                } else {
+                    self.factors
+                };
+                state.input_value = current
+                    .cloned()
+                    .unwrap_or_else(|| Factors::square(DEFAULT_SIZE))
+                    .to_string();
+                state.pending = Some(Pending::Range { session });
+            }
+            Event::ClearSessionRange => ev = self.on_session_factors.as_ref().map(|h| h(None)),
+            Event::OperationSelected(operation) => {
+                ev = self.on_operation.as_ref().map(|h| h(operation))
+            }
+            Event::QuestionKindSelected(kind) => {
+                ev = self.on_question_kind.as_ref().map(|h| h(kind))
+            }
+            Event::MultipleChoiceSelected(on) => {
+                ev = self.on_multiple_choice.as_ref().map(|h| h(on))
+            }
+            Event::CommutativeSelected(on) => ev = self.on_commutative.as_ref().map(|h| h(on)),
+            Event::OpenLongMultiplication => ev = self.on_long_multiplication.as_ref().map(|h| h()),
+            Event::OpenSkipCount => ev = self.on_skip_count.as_ref().map(|h| h()),
+            Event::StrategySelected(strategy) => {
+                ev = self.on_strategy.as_ref().map(|h| h(strategy))
+            }
+            Event::TimeLimitSelected(limit) => ev = self.on_time_limit.as_ref().map(|h| h(limit)),
+            Event::ColouringSelected(colouring) => {
+                ev = self.on_colouring.as_ref().map(|h| h(colouring))
+            }
+            Event::OpenBoxes => ev = self.on_boxes.as_ref().map(|h| h()),
+            Event::FluencyThresholdSelected(threshold) => {
+                ev = self.on_fluency_threshold.as_ref().map(|h| h(threshold))
+            }
+            Event::OpenWordProblems(language) => {
+                ev = self
+                    .on_word_problems
+                    .as_ref()
+                    .map(|h| h(language.to_owned()))
+            }
             Event::CloseModal => state.close(),
             Event::Noop => (),
             Event::ModalInput(s) => state.input_value = s,
+            Event::ModalSubmit if matches!(state.pending, Some(Pending::Range { .. })) => {
+                match state.input_value.parse::<Factors>() {
+                    Ok(factors) => {
+                        ev = match state.pending {
+                            Some(Pending::Range { session: true }) => {
+                                self.on_session_factors.as_ref().map(|h| h(Some(factors)))
+                            }
+                            _ => self.on_factors.as_ref().map(|h| h(factors)),
+                        };
+                        state.close();
+                    }
+                    Err(e) => state.error = Some(e.to_string()),
+                }
+            }
+            Event::ModalSubmit if state.pending.is_some() => {
+                let path = std::mem::take(&mut state.input_value);
+                ev = match state.pending.take() {
+                    Some(Pending::Archive(u)) => self.on_archive.as_ref().map(|h| h(u)),
+                    Some(Pending::Delete(u)) => self.on_delete.as_ref().map(|h| h(u)),
+                    Some(Pending::Export(scope)) => self.on_export.as_ref().map(|h| h(scope, path)),
+                    Some(Pending::Import) => self.on_import.as_ref().map(|h| h(path)),
+                    Some(Pending::Report(kind)) => self.on_report.as_ref().map(|h| h(kind, path)),
+                    Some(Pending::Range { .. }) | None => None,
+                };
+                state.close();
+            }
             Event::ModalSubmit => {
-                if self
-                    .user_list
-                    .unwrap()
-                    .iter()
-                    .any(|u| u == &state.input_value)
-                {
+                if let Err(e) = UserList::check_name(&state.input_value) {
+                    state.error = Some(e.to_string());
+                } else if self.name_taken(&state.input_value) {
                     state.error = Some("User with this name already exusts".into());
                 } else {
                     let v = state.input_value.clone();
@@ -146,10 +538,186 @@ impl<'u, Message> Component<Message, Renderer> for Menu<'u, Message> {
             .on_press_maybe(user_list_loaded.then_some(Event::OpenAddUserModal)));
         let rename_current_button = menu_tree!(base_button("Rename curent user")
             .on_press_maybe(user_list_loaded.then_some(Self::Event::OpenRenameModal)));
-        let menu = MenuBar::new(vec![first, add_user_button, rename_current_button])
-            .spacing(2.0)
-            .bounds_expand(30)
-            .cross_offset(16);
+        let remove_user_button =
+            user_submenu("Remove user", self.user_list, Self::Event::Noop, |u| {
+                vec![
+                    item("Archive", Self::Event::OpenArchiveModal(u)),
+                    item("Delete", Self::Event::OpenDeleteModal(u)),
+                ]
+            });
+        let archived_button =
+            user_submenu("Archived users", self.archived, Self::Event::Noop, |u| {
+                vec![
+                    item("Restore", Self::Event::RestoreUser(u)),
+                    item("Delete", Self::Event::OpenDeleteModal(u)),
+                ]
+            });
+        let transfer_button = if user_list_loaded {
+            menu_tree(
+                base_button("Transfer").on_press(Self::Event::Noop),
+                vec![
+                    item(
+                        "Export current user",
+                        Self::Event::OpenExportModal(ExportScope::Current),
+                    ),
+                    item(
+                        "Export all users",
+                        Self::Event::OpenExportModal(ExportScope::All),
+                    ),
+                    item("Import users", Self::Event::OpenImportModal),
+                    report_menu("Export scores", false),
+                    report_menu("Export scores and answers", true),
+                    item("Restore from backup", Self::Event::OpenBackups),
+                ],
+            )
+        } else {
+            menu_tree!(base_button("Transfer"))
+        };
+        let size_button = if user_list_loaded {
+            let mut children: Vec<_> = TABLE_SIZES
+                .iter()
+                .map(|&(label, size)| item(label, Self::Event::SizeSelected(size)))
+                .collect();
+            children.extend([
+                item(
+                    "Custom range",
+                    Self::Event::OpenRangeModal { session: false },
+                ),
+                item(
+                    "Range for this session",
+                    Self::Event::OpenRangeModal { session: true },
+                ),
+                item(
+                    "Back to the profile's range",
+                    Self::Event::ClearSessionRange,
+                ),
+                item(
+                    "Count 3 × 7 and 7 × 3 as one fact",
+                    Self::Event::CommutativeSelected(true),
+                ),
+                item(
+                    "Count 3 × 7 and 7 × 3 separately",
+                    Self::Event::CommutativeSelected(false),
+                ),
+            ]);
+            menu_tree(
+                base_button("Table size").on_press(Self::Event::Noop),
+                children,
+            )
+        } else {
+            menu_tree!(base_button("Table size"))
+        };
+        let operation_button = if user_list_loaded {
+            let mut children: Vec<_> = Operation::ALL
+                .iter()
+                .map(|&operation| {
+                    item(operation.label(), Self::Event::OperationSelected(operation))
+                })
+                .collect();
+            children.extend([
+                item("Long multiplication", Self::Event::OpenLongMultiplication),
+                item("Skip counting", Self::Event::OpenSkipCount),
+            ]);
+            if !self.languages.is_empty() {
+                children.push(menu_tree(
+                    base_button("Word problems")
+                        .on_press(Self::Event::Noop)
+                        .width(Length::Fill)
+                        .height(Length::Fill),
+                    self.languages
+                        .iter()
+                        .map(|&language| item(language, Self::Event::OpenWordProblems(language)))
+                        .collect(),
+                ));
+            }
+            menu_tree(
+                base_button("Operation").on_press(Self::Event::Noop),
+                children,
+            )
+        } else {
+            menu_tree!(base_button("Operation"))
+        };
+        let questions_button = if user_list_loaded {
+            let mut children: Vec<_> = QUESTION_KINDS
+                .iter()
+                .map(|&(label, kind)| item(label, Self::Event::QuestionKindSelected(kind)))
+                .collect();
+            children.extend([
+                item(
+                    "Type the answers",
+                    Self::Event::MultipleChoiceSelected(false),
+                ),
+                item(
+                    "Pick the answers from buttons",
+                    Self::Event::MultipleChoiceSelected(true),
+                ),
+            ]);
+            children.extend(
+                Strategy::ALL
+                    .iter()
+                    .map(|&s| item(s.label(), Self::Event::StrategySelected(s))),
+            );
+            children.push(menu_tree(
+                base_button("Time limit")
+                    .on_press(Self::Event::Noop)
+                    .width(Length::Fill)
+                    .height(Length::Fill),
+                TIME_LIMITS
+                    .iter()
+                    .map(|&(label, limit)| item(label, Self::Event::TimeLimitSelected(limit)))
+                    .collect(),
+            ));
+            menu_tree(
+                base_button("Questions").on_press(Self::Event::Noop),
+                children,
+            )
+        } else {
+            menu_tree!(base_button("Questions"))
+        };
+        let progress_button = if user_list_loaded {
+            let mut children = vec![
+                item(
+                    "Colour by score",
+                    Self::Event::ColouringSelected(Colouring::Score),
+                ),
+                item(
+                    "Colour by Leitner box",
+                    Self::Event::ColouringSelected(Colouring::LeitnerBox),
+                ),
+                item(
+                    "Colour by fluency",
+                    Self::Event::ColouringSelected(Colouring::Fluency),
+                ),
+            ];
+            children.extend(FLUENCY_THRESHOLDS.iter().map(|&(label, secs)| {
+                item(
+                    label,
+                    Self::Event::FluencyThresholdSelected(Duration::from_secs(secs)),
+                )
+            }));
+            children.push(item("Leitner boxes", Self::Event::OpenBoxes));
+            menu_tree(
+                base_button("Progress").on_press(Self::Event::Noop),
+                children,
+            )
+        } else {
+            menu_tree!(base_button("Progress"))
+        };
+        let menu = MenuBar::new(vec![
+            first,
+            add_user_button,
+            rename_current_button,
+            remove_user_button,
+            archived_button,
+            transfer_button,
+            size_button,
+            operation_button,
+            questions_button,
+            progress_button,
+        ])
+        .spacing(2.0)
+        .bounds_expand(30)
+        .cross_offset(16);
         let menu_row = iced::widget::row!(menu, iced::widget::horizontal_space(Length::Fill))
             .padding([2, 8])
             .align_items(alignment::Alignment::Center);
@@ -159,6 +727,9 @@ impl<'u, Message> Component<Message, Renderer> for Menu<'u, Message> {
             Self::Event::CloseModal,
         )
         .maybe_title(state.modal_title)
+        .maybe_message(state.pending.as_ref().map(Pending::message))
+        .show_input(state.pending.as_ref().is_none_or(Pending::needs_input))
+        .maybe_error(state.error.clone())
         .on_input(Self::Event::ModalInput)
         .on_submit(Self::Event::ModalSubmit)
         .into()
@@ -171,6 +742,93 @@ impl<'u, Message: 'u> From<Menu<'u, Message>> for Element<'u, Message, Renderer>
     }
 }
 
+const TABLE_SIZES: [(&str, usize); 5] = [
+    ("5 × 5", 5),
+    ("10 × 10", 10),
+    ("12 × 12", 12),
+    ("15 × 15", 15),
+    ("20 × 20", 20),
+];
+
+const FLUENCY_THRESHOLDS: [(&str, u64); 4] = [
+    ("Fluent within 2 s", 2),
+    ("Fluent within 3 s", 3),
+    ("Fluent within 5 s", 5),
+    ("Fluent within 8 s", 8),
+];
+
+const TIME_LIMITS: [(&str, Option<TimeLimit>); 6] = [
+    ("No time limit", None),
+    (
+        "5 s per question",
+        Some(TimeLimit::PerQuestion(Duration::from_secs(5))),
+    ),
+    (
+        "10 s per question",
+        Some(TimeLimit::PerQuestion(Duration::from_secs(10))),
+    ),
+    (
+        "30 s per set",
+        Some(TimeLimit::PerSet(Duration::from_secs(30))),
+    ),
+    (
+        "1 min per set",
+        Some(TimeLimit::PerSet(Duration::from_secs(60))),
+    ),
+    (
+        "2 min per set",
+        Some(TimeLimit::PerSet(Duration::from_secs(120))),
+    ),
+];
+
+const QUESTION_KINDS: [(&str, Option<QuestionKind>); 4] = [
+    ("Results, 7 × 8 = ?", Some(QuestionKind::Result)),
+    ("First number, ? × 8 = 56", Some(QuestionKind::MissingFirst)),
+    (
+        "Second number, 7 × ? = 56",
+        Some(QuestionKind::MissingSecond),
+    ),
+    ("Mixed", None),
+];
+
+const REPORT_FORMATS: [(&str, ReportFormat); 4] = [
+    (
+        "CSV, comma separated",
+        ReportFormat::Csv { delimiter: b',' },
+    ),
+    (
+        "CSV, semicolon separated",
+        ReportFormat::Csv { delimiter: b';' },
+    ),
+    ("TSV, tab separated", ReportFormat::Csv { delimiter: b'\t' }),
+    ("JSON", ReportFormat::Json),
+];
+
+/// Submenu picking the format of a score report
+fn report_menu<'a>(
+    label: &'a str,
+    with_attempts: bool,
+) -> iced_aw::MenuTree<'a, Event<'a>, Renderer> {
+    menu_tree(
+        base_button(label)
+            .on_press(Event::Noop)
+            .width(Length::Fill)
+            .height(Length::Fill),
+        REPORT_FORMATS
+            .iter()
+            .map(|&(label, format)| {
+                item(
+                    label,
+                    Event::OpenReportModal(ReportKind {
+                        format,
+                        with_attempts,
+                    }),
+                )
+            })
+            .collect(),
+    )
+}
+
 fn base_button<Message>(label: &str) -> button::Button<'_, Message, iced::Renderer> {
     button(
         text(label)
@@ -191,3 +849,31 @@ fn item<'a, Message: Clone + 'a>(
         .width(Length::Fill)
         .height(Length::Fill))
 }
+
+/// Menu listing `users`, each of them opening the submenu returned by `actions`
+fn user_submenu<'a, Message: Clone + 'a>(
+    label: &'a str,
+    users: Option<&'a Vec<String>>,
+    noop: Message,
+    actions: impl Fn(&'a str) -> Vec<iced_aw::MenuTree<'a, Message, Renderer>>,
+) -> iced_aw::MenuTree<'a, Message, Renderer> {
+    match users {
+        Some(users) if !users.is_empty() => {
+            let children: Vec<_> = users
+                .iter()
+                .map(|u| {
+                    menu_tree(
+                        base_button(u)
+                            .on_press(noop.clone())
+                            .width(Length::Fill)
+                            .height(Length::Fill),
+                        actions(u.as_str()),
+                    )
+                })
+                .collect();
+            // attach on_press to make the menu appear enabled
+            menu_tree(base_button(label).on_press(noop), children)
+        }
+        _ => menu_tree!(base_button(label)),
+    }
+}
@@ -1,6 +1,11 @@
+pub mod backup_list;
+pub mod box_board;
 pub mod cell;
 pub mod center_on_window;
 pub mod equation;
 pub mod input_modal;
+pub mod long_multiplication;
 pub mod menu;
 pub mod mult_table;
+pub mod skip_count;
+pub mod word_problem;
@@ -8,7 +8,10 @@ use iced::{
 use crate::{
     components::cell::text_cell,
     data::{
-        consts::{CELL_N, CELL_WIDTH, SPACING},
+        consts::{CELL_WIDTH, SPACING},
+        factors::Factors,
+        leitner::leitner_box,
+        operation::{Operation, QuestionKind},
         user::User,
     },
     helpers::{extend_col, extend_row},
@@ -17,40 +20,66 @@ use crate::{
 
 use super::equation::EqData;
 
+/// What the colours of the table cells show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Colouring {
+    /// share of right answers
+    #[default]
+    Score,
+    /// Leitner box the fact sits in
+    LeitnerBox,
+    /// share of answers that were both right and faster than the profile's fluency threshold
+    Fluency,
+}
+
 pub enum Hidden {
-    All,
     None,
-    Specified([[bool; CELL_N]; CELL_N]),
+    Specified(Vec<Vec<bool>>),
 }
 
 pub fn mult_table<'a, Message: 'a>(
     user: &'a Option<Arc<User>>,
+    factors: &Factors,
+    operation: Operation,
     hidden: &Hidden,
-    selected: &[EqData; CELL_N],
+    selected: &[EqData],
+    colouring: Colouring,
 ) -> Element<'a, Message, Renderer> {
-    let table_title = container(text("Tabliczka mnożenia")).center_x().width(
-        (SPACING + CELL_WIDTH) * CELL_N as u16 /* 10 cells with equations + 10 spacings */ + CELL_WIDTH,
+    let (cols, rows) = (factors.cols(), factors.rows());
+    let table_title = container(text(operation.table_title())).center_x().width(
+        (SPACING + CELL_WIDTH) * cols.clone().count() as u16 /* cells with equations + spacings */ + CELL_WIDTH,
     );
     let label_row = extend_row(
-        Row::new().push(text_cell('x').color(CellColor::DarkGrey)),
-        (1..=CELL_N).map(|n| text_cell(n).color(CellColor::Grey)),
+        Row::new().push(text_cell(operation.sign()).color(CellColor::DarkGrey)),
+        cols.clone().map(|n| text_cell(n).color(CellColor::Grey)),
     )
     .spacing(SPACING);
     let selected: Vec<_> = selected.iter().filter_map(|e| e.get_numbers()).collect();
+    let threshold = user
+        .as_ref()
+        .map(|u| u.fluency_threshold())
+        .unwrap_or_default();
     extend_col(
         Column::new().push(table_title).push(label_row),
-        (0..CELL_N).map(|j| {
-            let row_label = text_cell(j + 1).color(CellColor::Grey);
-            let table_cells = (0..CELL_N).map(|i| -> Element<'a, Message, Renderer> {
+        rows.map(|b| {
+            let j = b - 1;
+            let row_label = text_cell(b).color(CellColor::Grey);
+            let table_cells = cols.clone().map(|a| -> Element<'a, Message, Renderer> {
+                let i = a - 1;
                 let should_hide = match hidden {
-                    Hidden::All => true,
                     Hidden::None => false,
                     Hidden::Specified(s) => s[i][j],
                 };
-                let score = user.as_ref().map(|u| u.get_score(i, j));
+                let score = user.as_ref().and_then(|u| u.get_opt_score(operation, i, j));
                 let mut cell = match &score {
                     Some(s) if !should_hide => {
-                        text_cell((i + 1) * (j + 1)).color(s.as_ref().into())
+                        let color = match colouring {
+                            Colouring::Score => s.as_ref().into(),
+                            Colouring::LeitnerBox if s.tries == 0 => CellColor::White,
+                            Colouring::LeitnerBox => CellColor::of_box(leitner_box(s)),
+                            Colouring::Fluency => CellColor::of_percentage(s.fluency(threshold)),
+                        };
+                        text_cell(operation.table_value(a, b)).color(color)
                     }
                     _ => text_cell(""),
                 };
@@ -63,9 +92,27 @@ pub fn mult_table<'a, Message: 'a>(
                     return cell.into();
                 }
 
+                let score = score.unwrap();
+                let by_kind = QuestionKind::ALL.iter().filter_map(|kind| {
+                    let (tries, correct) = score.kind_counts(*kind);
+                    (tries != 0).then(|| format!("{}: {correct}/{tries}", kind.pattern(operation)))
+                });
                 tooltip(
                     cell,
-                    score.unwrap().to_string(),
+                    std::iter::once(score.to_string())
+                        .chain(by_kind)
+                        .chain((score.tries != 0).then(|| format!("box: {}", leitner_box(&score))))
+                        .chain(
+                            (score.time_outs() != 0)
+                                .then(|| format!("timed out: {}", score.time_outs())),
+                        )
+                        .chain(
+                            score
+                                .median_time()
+                                .map(|t| format!("median time: {:.1} s", t.as_secs_f32())),
+                        )
+                        .collect::<Vec<_>>()
+                        .join("\n"),
                     tooltip::Position::FollowCursor,
                 )
                 .style(iced::theme::Container::Custom(Box::new(
@@ -0,0 +1,122 @@
+use iced::widget::{component, Column, Component, Row};
+use iced::{Element, Renderer};
+
+use crate::components::{
+    cell::text_cell,
+    equation::{answer_input, parse_answer, CheckState, EqData},
+};
+use crate::data::{consts::SPACING, operation::Operation, skip_count::SkipCount};
+use crate::helpers::extend_row;
+use crate::styles::cell::CellColor;
+
+pub struct SkipCountView<'a, Message> {
+    data: &'a SkipCount,
+    /// one per blank, in the order they're answered
+    answers: &'a [EqData],
+    show_checked: bool,
+    on_input: Box<dyn Fn(usize, Option<u32>) -> Message>,
+    on_submit: Box<dyn Fn(usize) -> Message>,
+}
+
+/// Row of the table as a strip of cells, only the first empty blank and the ones before it take answers
+pub fn skip_count<'a, Message>(
+    data: &'a SkipCount,
+    answers: &'a [EqData],
+    show_checked: bool,
+    on_input: impl Fn(usize, Option<u32>) -> Message + 'static,
+    on_submit: impl Fn(usize) -> Message + 'static,
+) -> SkipCountView<'a, Message> {
+    SkipCountView {
+        data,
+        answers,
+        show_checked,
+        on_input: Box::new(on_input),
+        on_submit: Box::new(on_submit),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Input(usize, String),
+    Submit(usize),
+}
+
+impl<'a, Message> SkipCountView<'a, Message> {
+    /// Number counted at `column`, or the input of its blank
+    fn cell(&self, column: usize, next_blank: usize) -> Element<'a, Event, Renderer> {
+        let number = self.data.columns()[column] * self.data.row();
+        let Some(blank) = self.data.blank_at(column) else {
+            return text_cell(number).into();
+        };
+        if blank > next_blank {
+            // answered in order, later blanks wait for the earlier ones
+            return text_cell("").disabled(true).into();
+        }
+        let eq_data = &self.answers[blank];
+        let check_state = if self.show_checked {
+            &eq_data.correctness
+        } else {
+            &CheckState::Unckecked
+        };
+        let mut input = answer_input(eq_data.value, check_state.into());
+        if !self.show_checked {
+            input = input
+                .on_input(move |v| Event::Input(blank, v))
+                .on_submit(Event::Submit(blank));
+        }
+        input.into()
+    }
+}
+
+impl<'a, Message> Component<Message, Renderer> for SkipCountView<'a, Message> {
+    type State = ();
+    type Event = Event;
+
+    fn update(&mut self, _state: &mut Self::State, event: Event) -> Option<Message> {
+        match event {
+            Event::Input(blank, value) => {
+                let current = self.answers[blank].value;
+                let new_value = parse_answer(&value, current);
+                (new_value != current).then(|| (self.on_input)(blank, new_value))
+            }
+            Event::Submit(blank) => Some((self.on_submit)(blank)),
+        }
+    }
+
+    fn view(&self, _state: &Self::State) -> Element<'_, Event, Renderer> {
+        let row = self.data.row();
+        let next_blank = self
+            .answers
+            .iter()
+            .position(|e| e.value.is_none())
+            .unwrap_or(self.answers.len());
+        let label_row = extend_row(
+            Row::new().push(text_cell(Operation::Multiplication.sign()).color(CellColor::DarkGrey)),
+            self.data
+                .columns()
+                .iter()
+                .map(|&a| text_cell(a).color(CellColor::Grey)),
+        )
+        .spacing(SPACING);
+        let cells = (0..self.data.columns().len()).map(|column| self.cell(column, next_blank));
+        let strip = extend_row(
+            Row::new().push(text_cell(row).color(CellColor::Grey)),
+            cells,
+        )
+        .spacing(SPACING);
+        Column::new()
+            .push(label_row)
+            .push(strip)
+            .spacing(SPACING)
+            .into()
+    }
+}
+
+impl<'a, Message> From<SkipCountView<'a, Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(view: SkipCountView<'a, Message>) -> Self {
+        component(view)
+    }
+}
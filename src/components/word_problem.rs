@@ -0,0 +1,81 @@
+use iced::widget::{column, component, container, row, text, Component};
+use iced::{Element, Length, Renderer};
+
+use crate::components::equation::{answer_input, parse_answer, CheckState, EqData};
+
+use crate::helpers::centered_text;
+
+pub struct WordProblemView<'a, Message> {
+    text: &'a str,
+    eq_data: EqData,
+    show_checked: bool,
+    on_change: Box<dyn Fn(Option<u32>) -> Message>,
+    on_submit: Box<dyn Fn() -> Message>,
+}
+
+/// Problem text with an input for its answer, `eq_data` holds the fact it asks about
+pub fn word_problem<'a, Message>(
+    text: &'a str,
+    eq_data: EqData,
+    show_checked: bool,
+    on_change: impl Fn(Option<u32>) -> Message + 'static,
+    on_submit: impl Fn() -> Message + 'static,
+) -> WordProblemView<'a, Message> {
+    WordProblemView {
+        text,
+        eq_data,
+        show_checked,
+        on_change: Box::new(on_change),
+        on_submit: Box::new(on_submit),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    InputChanged(String),
+    FocusNext,
+}
+
+impl<'a, Message> Component<Message, Renderer> for WordProblemView<'a, Message> {
+    type State = ();
+    type Event = Event;
+
+    fn update(&mut self, _state: &mut Self::State, event: Event) -> Option<Message> {
+        match event {
+            Event::InputChanged(value) => {
+                let new_value = parse_answer(&value, self.eq_data.value);
+                (new_value != self.eq_data.value).then(|| (self.on_change)(new_value))
+            }
+            Event::FocusNext => Some((self.on_submit)()),
+        }
+    }
+
+    fn view(&self, _state: &Self::State) -> Element<'_, Event, Renderer> {
+        let check_state = if self.show_checked {
+            &self.eq_data.correctness
+        } else {
+            &CheckState::Unckecked
+        };
+        let mut input = answer_input(self.eq_data.value, check_state.into());
+        if !self.show_checked {
+            input = input
+                .on_input(Event::InputChanged)
+                .on_submit(Event::FocusNext);
+        }
+        column![
+            text(self.text).width(Length::Fill),
+            row![container(centered_text('=')).height(35).center_y(), input].spacing(10),
+        ]
+        .spacing(5)
+        .into()
+    }
+}
+
+impl<'a, Message> From<WordProblemView<'a, Message>> for Element<'a, Message, Renderer>
+where
+    Message: 'a,
+{
+    fn from(view: WordProblemView<'a, Message>) -> Self {
+        component(view)
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Result};
+
+use super::{
+    consts::app_dir,
+    persist::DirLock,
+    store::{FsStore, ProfileStore},
+    user::User,
+    user_list::UserList,
+    versioned::QUARANTINE_DIR,
+};
+
+pub const BACKUP_DIR: &str = "backups";
+/// Snapshots kept when no retention is configured
+pub const DEFAULT_RETENTION: usize = 10;
+/// Environment variable overriding the number of kept snapshots, see the readme
+pub const RETENTION_VAR: &str = "MULT_TABLE_BACKUPS";
+
+/// Copy of the data directory taken at one point in time
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    path: PathBuf,
+    created: SystemTime,
+    users: Vec<String>,
+}
+
+impl Snapshot {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn created(&self) -> SystemTime {
+        self.created
+    }
+
+    /// Active and archived users the snapshot holds
+    pub fn users(&self) -> &[String] {
+        &self.users
+    }
+
+    fn read(path: PathBuf) -> Option<Self> {
+        // snapshot directories are named `<seconds since epoch>[-n]`
+        let secs = path
+            .file_name()?
+            .to_str()?
+            .split('-')
+            .next()?
+            .parse()
+            .ok()?;
+        let store = snapshot_store(&path).ok()?;
+        let mut users = store.list_profiles().unwrap_or_default();
+        users.extend(store.list_archived_profiles().unwrap_or_default());
+        Some(Self {
+            created: UNIX_EPOCH + Duration::from_secs(secs),
+            users,
+            path,
+        })
+    }
+}
+
+/// Rotating snapshots of a data directory, kept in its `backups` subdirectory
+#[derive(Debug, Clone)]
+pub struct Backups {
+    root: PathBuf,
+    retention: usize,
+}
+
+impl Backups {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            retention: DEFAULT_RETENTION,
+        }
+    }
+
+    /// Number of snapshots kept, older ones are removed when a new one is taken
+    pub fn retention(mut self, retention: usize) -> Self {
+        self.retention = retention.max(1);
+        self
+    }
+
+    fn dir(&self) -> PathBuf {
+        self.root.join(BACKUP_DIR)
+    }
+
+    /// Copies the data directory into a new snapshot and drops the ones over the retention.
+    /// Returns `Ok(None)` if there was nothing to back up yet
+    pub fn snapshot(&self) -> Result<Option<PathBuf>> {
+        if !self.root.exists() {
+            return Ok(None);
+        }
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut to = self.dir().join(secs.to_string());
+        for n in 1.. {
+            if !to.exists() {
+                break;
+            }
+            to = self.dir().join(format!("{secs}-{n}"));
+        }
+        {
+            let _lock = DirLock::acquire(&self.root)?;
+            if !copy_data(&self.root, &to)? {
+                return Ok(None);
+            }
+        }
+        self.prune()?;
+        Ok(Some(to))
+    }
+
+    /// Snapshots sorted from the newest
+    pub fn list(&self) -> Result<Vec<Snapshot>> {
+        let entries = match std::fs::read_dir(self.dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        let mut snapshots = vec![];
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                snapshots.extend(Snapshot::read(entry.path()));
+            }
+        }
+        snapshots.sort_by(|a, b| b.created.cmp(&a.created).then(b.path.cmp(&a.path)));
+        Ok(snapshots)
+    }
+
+    fn prune(&self) -> Result<()> {
+        for old in self.list()?.iter().skip(self.retention) {
+            std::fs::remove_dir_all(&old.path)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces every profile in `store` with the ones from `snapshot`, all at once.
+    /// The current state is backed up first, so the restore itself can be undone
+    pub fn restore(&self, store: &dyn ProfileStore, snapshot: &Path) -> Result<UserList> {
+        // read before the backup below, which prunes the snapshot if it's the oldest one
+        let (active, archived, list) =
+            read_snapshot(snapshot).map_err(|e| e.context("the backup can't be restored"))?;
+        self.snapshot()
+            .map_err(|e| e.context("couldn't back up the current profiles before restoring"))?;
+        store.replace_all(&active, &archived, &list)?;
+        Ok(list)
+    }
+}
+
+impl Default for Backups {
+    fn default() -> Self {
+        let backups = Self::new(app_dir());
+        match std::env::var(RETENTION_VAR).map(|v| v.parse()) {
+            Ok(Ok(retention)) => backups.retention(retention),
+            _ => backups,
+        }
+    }
+}
+
+/// Active and archived profiles of a snapshot with its user list,
+/// all of them are read so that a snapshot that can't be restored whole changes nothing
+fn read_snapshot(dir: &Path) -> Result<(Vec<User>, Vec<User>, UserList)> {
+    let from = snapshot_store(dir)?;
+    let active_names = from.list_profiles()?;
+    let archived_names = from.list_archived_profiles()?;
+    let missing = |name: &str| anyhow!("profile {name} is missing from the backup");
+    let active = active_names
+        .iter()
+        .map(|name| from.load_profile(name)?.ok_or_else(|| missing(name)))
+        .collect::<Result<_>>()?;
+    let archived = archived_names
+        .iter()
+        .map(|name| {
+            from.load_archived_profile(name)?
+                .ok_or_else(|| missing(name))
+        })
+        .collect::<Result<_>>()?;
+    let list = match from.load_user_list()? {
+        Some(list) => list,
+        None => UserList::from_profiles(active_names, archived_names),
+    };
+    Ok((active, archived, list))
+}
+
+/// Opens a snapshot the same way the app opens its data directory, without changing it
+fn snapshot_store(dir: &Path) -> Result<Box<dyn ProfileStore>> {
+    #[cfg(feature = "sqlite")]
+    {
+        use super::store::{SqliteStore, DB_FILE};
+        if dir.join(DB_FILE).exists() {
+            return Ok(Box::new(SqliteStore::open_copy(dir.join(DB_FILE))?));
+        }
+    }
+    Ok(Box::new(FsStore::read_only(dir)))
+}
+
+/// Recursively copies the data files from `from` into `to`, skipping backups,
+/// quarantined files and leftovers of interrupted writes.
+/// The database is copied through SQLite, the store doesn't take the directory lock.
+/// Returns whether anything was copied
+fn copy_data(from: &Path, to: &Path) -> Result<bool> {
+    let mut copied = false;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name == BACKUP_DIR || name == QUARANTINE_DIR || name.starts_with('.') {
+            continue;
+        }
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copied |= copy_data(&entry.path(), &target)?;
+        } else if !name.ends_with(".tmp") {
+            std::fs::create_dir_all(to)?;
+            copy_file(&entry.path(), &target)?;
+            copied = true;
+        }
+    }
+    Ok(copied)
+}
+
+#[cfg(feature = "sqlite")]
+fn copy_file(from: &Path, to: &Path) -> Result<()> {
+    use super::store::{SqliteStore, DB_FILE};
+    match from.file_name().and_then(|n| n.to_str()) {
+        Some(DB_FILE) => SqliteStore::copy_database(from, to),
+        // the journal and the WAL are part of the copy of the database
+        Some(name) if name.starts_with(DB_FILE) => Ok(()),
+        _ => Ok(std::fs::copy(from, to).map(|_| ())?),
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn copy_file(from: &Path, to: &Path) -> Result<()> {
+    std::fs::copy(from, to)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("mult_table_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        root
+    }
+
+    #[test]
+    fn broken_snapshot_leaves_the_profiles_alone() {
+        let root = temp_root("broken_snapshot");
+        let store = FsStore::new(&root);
+        let backups = Backups::new(&root);
+        store.create_profile(&User::new("A")).unwrap();
+        let snapshot = backups.snapshot().unwrap().unwrap();
+        std::fs::write(snapshot.join("A.ron"), "not a profile").unwrap();
+        store.create_profile(&User::new("B")).unwrap();
+
+        assert!(backups.restore(&store, &snapshot).is_err());
+        assert_eq!(store.list_profiles().unwrap(), ["A", "B"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn snapshots_the_database_through_sqlite() {
+        use crate::data::store::{SqliteStore, DB_FILE};
+        let root = temp_root("sqlite_snapshot");
+        let store = SqliteStore::open_in(&root).unwrap();
+        store.create_profile(&User::new("A")).unwrap();
+        let snapshot = Backups::new(&root).snapshot().unwrap().unwrap();
+        store.create_profile(&User::new("B")).unwrap();
+
+        let copy = SqliteStore::open(snapshot.join(DB_FILE)).unwrap();
+        assert_eq!(copy.list_profiles().unwrap(), ["A"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn restores_the_snapshot() {
+        let root = temp_root("restore");
+        let store = FsStore::new(&root);
+        let backups = Backups::new(&root);
+        store.create_profile(&User::new("A")).unwrap();
+        let snapshot = backups.snapshot().unwrap().unwrap();
+        store.create_profile(&User::new("B")).unwrap();
+
+        backups.restore(&store, &snapshot).unwrap();
+        assert_eq!(store.list_profiles().unwrap(), ["A"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn restores_archived_profiles_without_changing_the_snapshot() {
+        let root = temp_root("restore_archived");
+        let store = FsStore::new(&root);
+        let backups = Backups::new(&root);
+        store.create_profile(&User::new("A")).unwrap();
+        store.archive_profile("A").unwrap();
+        let snapshot = backups.snapshot().unwrap().unwrap();
+        store.delete_profile("A").unwrap();
+
+        assert_eq!(backups.list().unwrap()[0].users(), ["A"]);
+        backups.restore(&store, &snapshot).unwrap();
+        assert_eq!(store.list_archived_profiles().unwrap(), ["A"]);
+        let snapshot = FsStore::new(&snapshot);
+        assert_eq!(snapshot.list_archived_profiles().unwrap(), ["A"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
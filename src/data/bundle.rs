@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use ron::Value;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    consts::export_dir,
+    persist::write_atomically,
+    store::ProfileStore,
+    user::User,
+    user_list::UserList,
+    versioned::{
+        from_versioned_bytes, from_versioned_value, to_versioned_string, to_versioned_value,
+        Migration, Versioned,
+    },
+};
+
+/// File extension of exported profile bundles
+pub const BUNDLE_EXTENSION: &str = "multbundle";
+
+/// Where bundles are exported to and imported from unless the user picks another file
+pub fn default_bundle_path() -> PathBuf {
+    export_dir().join(format!("mult_table.{BUNDLE_EXTENSION}"))
+}
+
+/// Profiles with their whole history, packed into one file to move them between computers
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    profiles: Vec<User>,
+}
+
+/// Layout of a bundle file. Every profile keeps its own versioned envelope,
+/// so profiles bundled by older versions of the app are migrated like profile files
+#[derive(Serialize, Deserialize)]
+struct BundleFile {
+    profiles: Vec<Value>,
+}
+
+impl Versioned for BundleFile {
+    const MIGRATIONS: &'static [Migration] = &[];
+}
+
+/// What to do with a bundled profile whose name is already taken
+#[derive(Debug, Clone)]
+pub enum Resolution {
+    /// import it as a separate profile with the given name
+    Rename(String),
+    /// add its scores to the existing profile
+    Merge,
+    /// leave it out, keeping the existing profile as it is
+    Skip,
+}
+
+impl Bundle {
+    pub fn profiles(&self) -> &[User] {
+        &self.profiles
+    }
+
+    pub fn into_profiles(self) -> Vec<User> {
+        self.profiles
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write_atomically(path, self.to_string()?.as_bytes())
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let file: BundleFile = from_versioned_bytes(bytes)?;
+        let profiles = file
+            .profiles
+            .into_iter()
+            .map(from_versioned_value)
+            .collect::<Result<_>>()?;
+        Ok(Self { profiles })
+    }
+
+    fn to_string(&self) -> Result<String> {
+        let profiles = self
+            .profiles
+            .iter()
+            .map(to_versioned_value)
+            .collect::<Result<_>>()?;
+        to_versioned_string(&BundleFile { profiles })
+    }
+}
+
+/// Writes the named profiles from `store` into a bundle at `path`
+pub fn export_profiles(store: &dyn ProfileStore, names: &[String], path: &Path) -> Result<()> {
+    let profiles = names
+        .iter()
+        .map(|name| {
+            store
+                .load_profile(name)?
+                .ok_or_else(|| anyhow!("profile {name} doesn't exist"))
+        })
+        .collect::<Result<_>>()?;
+    Bundle { profiles }.write(path)
+}
+
+/// Adds one bundled profile to `store` and `list`.
+/// `resolution` says what to do if the profile's name is already taken,
+/// `None` expects it to be free
+pub fn import_profile(
+    store: &dyn ProfileStore,
+    list: &mut UserList,
+    mut user: User,
+    resolution: Option<Resolution>,
+) -> Result<()> {
+    let Some(resolution) = resolution.filter(|_| list.contains(user.name())) else {
+        // names in a bundle come from outside, they get the same checks as typed ones
+        list.check_new_name(user.name())?;
+        store.create_profile(&user)?;
+        list.insert_user(user.name());
+        return Ok(());
+    };
+    match resolution {
+        Resolution::Skip => Ok(()),
+        Resolution::Merge => {
+            let mut existing = store.load_profile(user.name())?.ok_or_else(|| {
+                anyhow!(
+                    "{} is archived, restore it before merging into it",
+                    user.name()
+                )
+            })?;
+            existing.merge(&user);
+            store.save_profile(&existing)
+        }
+        Resolution::Rename(new_name) => {
+            list.check_new_name(&new_name)?;
+            user.set_name(new_name);
+            store.create_profile(&user)?;
+            list.insert_user(user.name());
+            Ok(())
+        }
+    }
+}
+
+/// Imports every profile of the bundle at `path`, asking `resolve` about taken names
+pub fn import_bundle(
+    store: &dyn ProfileStore,
+    list: &mut UserList,
+    path: &Path,
+    mut resolve: impl FnMut(&User) -> Resolution,
+) -> Result<()> {
+    for user in Bundle::read(path)?.into_profiles() {
+        let resolution = list.contains(user.name()).then(|| resolve(&user));
+        import_profile(store, list, user, resolution)?;
+    }
+    store.save_user_list(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::data::{
+        operation::{Operation, QuestionKind},
+        score::Attempt,
+        store::MemoryStore,
+    };
+
+    #[test]
+    fn refuses_names_leading_out_of_the_store() {
+        let store = MemoryStore::new();
+        let mut list = UserList::default();
+        let user = User::new("../escaped");
+        assert!(import_profile(&store, &mut list, user, None).is_err());
+        assert!(!list.contains("../escaped"));
+        assert!(store.list_profiles().unwrap().is_empty());
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut a = User::new("A");
+        let attempt = Attempt::new(Some(6), true, Some(Duration::from_millis(1500)))
+            .kind(QuestionKind::MissingFirst);
+        let fluency = a.fluency_threshold();
+        a.get_mut_score(Operation::Division, 1, 2)
+            .unwrap()
+            .record(attempt, fluency);
+        let bundle = Bundle {
+            profiles: vec![a, User::new("B")],
+        };
+        let read = Bundle::from_bytes(bundle.to_string().unwrap().as_bytes()).unwrap();
+        let names: Vec<_> = read.profiles().iter().map(User::name).collect();
+        assert_eq!(names, ["A", "B"]);
+        let score = read.profiles()[0]
+            .get_opt_score(Operation::Division, 1, 2)
+            .unwrap();
+        assert_eq!(score.history(), [attempt]);
+        assert!(score.review().is_some());
+    }
+}
@@ -0,0 +1,109 @@
+use rand::{seq::SliceRandom, Rng};
+
+use super::operation::{Operation, QuestionKind};
+
+/// Number of answers offered in the multiple choice mode, the right one included
+pub const CHOICES: usize = 4;
+
+/// Answers to pick from for the fact `(a, b)`, in random order.
+///
+/// The wrong ones are mistakes children actually make: a factor off by one,
+/// a neighbouring cell of the table and swapped digits
+pub fn choices(
+    operation: Operation,
+    kind: QuestionKind,
+    a: u32,
+    b: u32,
+    rng: &mut impl Rng,
+) -> [u32; CHOICES] {
+    let answer = kind.answer(operation, a, b);
+    let neighbour = |da: i64, db: i64| {
+        let (a, b) = (a as i64 + da, b as i64 + db);
+        (a > 0 && b > 0).then(|| kind.answer(operation, a as u32, b as u32))
+    };
+    let mut off_by_one: Vec<_> = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+        .into_iter()
+        .filter_map(|(da, db)| neighbour(da, db))
+        .collect();
+    let mut diagonal: Vec<_> = [(-1, -1), (-1, 1), (1, -1), (1, 1)]
+        .into_iter()
+        .filter_map(|(da, db)| neighbour(da, db))
+        .collect();
+    off_by_one.shuffle(rng);
+    diagonal.shuffle(rng);
+    // close answers fill in for the neighbours missing at the edges of the table
+    let near = [answer + 1, answer.saturating_sub(1), answer + 2, answer + 3];
+    let candidates = swapped_digits(answer)
+        .into_iter()
+        .chain(off_by_one)
+        .chain(diagonal)
+        .chain(near);
+
+    let mut picked = [answer; CHOICES];
+    let mut count = 1;
+    for candidate in candidates {
+        if count == CHOICES {
+            break;
+        }
+        if candidate != 0 && !picked[..count].contains(&candidate) {
+            picked[count] = candidate;
+            count += 1;
+        }
+    }
+    picked.shuffle(rng);
+    picked
+}
+
+/// `56` -> `65`, numbers reading the same both ways have no swap
+fn swapped_digits(n: u32) -> Option<u32> {
+    let swapped: u32 = n
+        .to_string()
+        .chars()
+        .rev()
+        .collect::<String>()
+        .parse()
+        .ok()?;
+    (n >= 10 && swapped != n).then_some(swapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn offers_the_answer_once_among_distinct_ones() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for operation in Operation::ALL {
+            for kind in QuestionKind::ALL {
+                for (a, b) in (1..=12).flat_map(|a| (1..=12).map(move |b| (a, b))) {
+                    let picked = choices(operation, kind, a, b, &mut rng);
+                    let answer = kind.answer(operation, a, b);
+                    assert_eq!(picked.iter().filter(|&&c| c == answer).count(), 1);
+                    assert!(picked.iter().all(|&c| c != 0));
+                    for (i, c) in picked.iter().enumerate() {
+                        assert!(!picked[i + 1..].contains(c), "{picked:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swapped_digits_are_a_likely_mistake() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let picked = choices(
+            Operation::Multiplication,
+            QuestionKind::Result,
+            7,
+            8,
+            &mut rng,
+        );
+        assert!(picked.contains(&65));
+        assert_eq!(swapped_digits(56), Some(65));
+        assert_eq!(swapped_digits(44), None);
+        assert_eq!(swapped_digits(7), None);
+        assert_eq!(swapped_digits(10), Some(1));
+    }
+}
@@ -1,15 +1,30 @@
 use std::path::PathBuf;
 
 use platform_dirs::AppDirs;
-pub const CELL_N: usize = 10;
+/// Table size of new profiles
+pub const DEFAULT_SIZE: usize = 10;
+/// Number of equations asked at once
+pub const SET_SIZE: usize = 10;
 pub const CELL_WIDTH: u16 = 35;
 pub const SPACING: u16 = 10;
+/// Number of word problems asked at once
+pub const WORD_PROBLEMS: usize = 5;
+/// Right answers given within this many milliseconds count as fluent in new profiles
+pub const DEFAULT_FLUENCY_MS: u32 = 3000;
 pub fn app_dir() -> PathBuf {
     AppDirs::new(Some("rust_mult_table"), false)
         .unwrap()
         .config_dir
 }
 
-pub async fn create_app_dir() {
-    let _ = tokio::fs::create_dir_all(app_dir()).await;
+/// Where teachers put their own word problem templates
+pub fn templates_dir() -> PathBuf {
+    app_dir().join("templates")
+}
+
+/// Where exported files are suggested to be saved
+pub fn export_dir() -> PathBuf {
+    dirs_next::document_dir()
+        .or_else(dirs_next::home_dir)
+        .unwrap_or_default()
 }
@@ -0,0 +1,168 @@
+use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
+
+use anyhow::{anyhow, bail, Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Biggest factor a range can reach, keeps the table small enough to show
+pub const MAX_FACTOR: usize = 30;
+
+/// Rectangle of the table that is practiced: first factors are the columns, second ones the rows
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "RawFactors")]
+pub struct Factors {
+    cols: RangeInclusive<usize>,
+    rows: RangeInclusive<usize>,
+}
+
+/// Factors as stored, read files get the same checks as [`Factors::new`]
+#[derive(Deserialize)]
+struct RawFactors {
+    cols: RangeInclusive<usize>,
+    rows: RangeInclusive<usize>,
+}
+
+impl TryFrom<RawFactors> for Factors {
+    type Error = Error;
+
+    fn try_from(raw: RawFactors) -> Result<Self> {
+        Self::new(raw.cols, raw.rows)
+    }
+}
+
+impl Factors {
+    pub fn new(cols: RangeInclusive<usize>, rows: RangeInclusive<usize>) -> Result<Self> {
+        for range in [&cols, &rows] {
+            if *range.start() == 0 || range.start() > range.end() || *range.end() > MAX_FACTOR {
+                bail!(
+                    "{}–{} isn't a range between 1 and {MAX_FACTOR}",
+                    range.start(),
+                    range.end()
+                );
+            }
+        }
+        Ok(Self { cols, rows })
+    }
+
+    /// The whole `1..=n` × `1..=n` table
+    pub fn square(n: usize) -> Self {
+        Self {
+            cols: 1..=n,
+            rows: 1..=n,
+        }
+    }
+
+    /// Range of the first factor
+    pub fn cols(&self) -> RangeInclusive<usize> {
+        self.cols.clone()
+    }
+
+    /// Range of the second factor
+    pub fn rows(&self) -> RangeInclusive<usize> {
+        self.rows.clone()
+    }
+
+    pub fn contains(&self, a: usize, b: usize) -> bool {
+        self.cols.contains(&a) && self.rows.contains(&b)
+    }
+
+    /// Biggest factor of either range
+    pub fn max(&self) -> usize {
+        *self.cols.end().max(self.rows.end())
+    }
+
+    /// Number of facts in the rectangle
+    pub fn count(&self) -> usize {
+        self.cols.clone().count() * self.rows.clone().count()
+    }
+}
+
+fn parse_range(s: &str) -> Result<RangeInclusive<usize>> {
+    let s = s.trim();
+    let (start, end) = s
+        .split_once("..=")
+        .or_else(|| s.split_once(".."))
+        .or_else(|| s.split_once(['-', '–']))
+        .unwrap_or((s, s));
+    let number = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|_| anyhow!("\"{}\" isn't a number", n.trim()))
+    };
+    Ok(number(start)?..=number(end)?)
+}
+
+/// Parses ranges like `6-9 x 1-12` or `1..10 × 11..12`, a single number stands for itself
+impl FromStr for Factors {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (cols, rows) = s
+            .split_once(['x', 'X', '×', '*'])
+            .ok_or_else(|| anyhow!("expected two ranges separated by \"x\", like 6-9 x 1-12"))?;
+        Self::new(parse_range(cols)?, parse_range(rows)?)
+    }
+}
+
+impl Display for Factors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}–{} × {}–{}",
+            self.cols.start(),
+            self.cols.end(),
+            self.rows.start(),
+            self.rows.end()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ranges_in_every_notation() {
+        let expected = Factors::new(6..=9, 1..=12).unwrap();
+        for s in ["6-9 x 1-12", "6..9 × 1..12", "6..=9*1..=12", " 6–9 X 1-12 "] {
+            assert_eq!(s.parse::<Factors>().unwrap(), expected, "{s}");
+        }
+        assert_eq!(
+            "7 x 3-4".parse::<Factors>().unwrap(),
+            Factors::new(7..=7, 3..=4).unwrap()
+        );
+    }
+
+    #[test]
+    fn refuses_ranges_it_can_not_show() {
+        for s in [
+            "6-9",
+            "0-3 x 1-2",
+            "5-2 x 1-2",
+            "1-31 x 1-2",
+            "a-3 x 1-2",
+            "",
+        ] {
+            assert!(s.parse::<Factors>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn checks_ranges_read_from_files() {
+        let factors = Factors::new(2..=5, 3..=12).unwrap();
+        let read: Factors = ron::from_str(&ron::to_string(&factors).unwrap()).unwrap();
+        assert_eq!(read, factors);
+        for s in [
+            "(cols:(start:0,end:3),rows:(start:1,end:2))",
+            "(cols:(start:3,end:2),rows:(start:1,end:2))",
+            "(cols:(start:1,end:3),rows:(start:1,end:31))",
+        ] {
+            assert!(ron::from_str::<Factors>(s).is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn displays_what_it_parses() {
+        let factors = Factors::new(2..=5, 3..=12).unwrap();
+        assert_eq!(factors.to_string().parse::<Factors>().unwrap(), factors);
+    }
+}
@@ -0,0 +1,48 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{schedule::Priority, score::Score};
+
+/// Number of Leitner boxes, facts start in the first one
+pub const BOXES: usize = 5;
+/// Days between reviews of the facts in each box
+pub const REVIEW_DAYS: [u64; BOXES] = [1, 2, 4, 8, 16];
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Box the fact sits in, 1 to [`BOXES`]: one up for every right answer in a row, back to 1 after a miss
+pub fn leitner_box(score: &Score) -> usize {
+    let streak = score
+        .history()
+        .iter()
+        .rev()
+        .take_while(|a| a.is_correct())
+        .count();
+    1 + streak.min(BOXES - 1)
+}
+
+/// Where the fact goes when drawing a test at `now`, facts in higher boxes come back less often
+pub fn priority(score: &Score, now: SystemTime) -> Priority {
+    match score.history().last() {
+        Some(last) => {
+            let wait = Duration::from_secs(REVIEW_DAYS[leitner_box(score) - 1] * DAY);
+            let due = last.timestamp() + wait;
+            if due <= now {
+                Priority::Due(due)
+            } else {
+                Priority::Later(due)
+            }
+        }
+        None if score.tries == 0 => Priority::New,
+        // counted before the answer log existed, so there's nothing to place it by
+        None => Priority::Due(UNIX_EPOCH),
+    }
+}
+
+/// Number of answered facts in every box, first box first
+pub fn box_counts<'s>(scores: impl IntoIterator<Item = &'s Score>) -> [usize; BOXES] {
+    let mut counts = [0; BOXES];
+    for score in scores.into_iter().filter(|s| s.tries != 0) {
+        counts[leitner_box(score) - 1] += 1;
+    }
+    counts
+}
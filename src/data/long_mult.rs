@@ -0,0 +1,336 @@
+use rand::Rng;
+
+/// What a single input cell of the written multiplication holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    /// digit carried into a column of a partial product, an empty cell counts as 0
+    Carry,
+    /// digit of a partial product or of the final sum
+    Digit,
+}
+
+/// One cell to fill in, in the order it's worked out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    row: usize,
+    column: usize,
+    slot: Slot,
+    expected: u32,
+    /// index into [`LongMultiplication::facts`] of the table fact the cell comes from
+    fact: Option<usize>,
+}
+
+impl Step {
+    /// Partial product the cell belongs to, the one after the last is the final sum
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Place value of the cell, counted from the right starting at 0
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+}
+
+/// Written multiplication of `top` by `bottom`, filled in digit by digit.
+///
+/// Every digit of a partial product comes from a single table fact, e.g. `3 × 7` in `23 × 7`,
+/// so answers can be scored like the facts of the table
+#[derive(Debug, Clone)]
+pub struct LongMultiplication {
+    top: u32,
+    bottom: u32,
+    steps: Vec<Step>,
+    /// `(digit of top, digit of bottom)` of every product worked out
+    facts: Vec<(u32, u32)>,
+    answers: Vec<Option<u32>>,
+    /// number of leading steps found correct by the last check
+    correct: usize,
+    first_wrong: Option<usize>,
+    recorded: Vec<bool>,
+}
+
+impl LongMultiplication {
+    pub fn new(top: u32, bottom: u32) -> Self {
+        let (top_digits, bottom_digits) = (digits(top), digits(bottom));
+        let mut steps = vec![];
+        let mut facts = vec![];
+        for (row, &b) in bottom_digits.iter().enumerate() {
+            let mut carry = 0;
+            for (i, &a) in top_digits.iter().enumerate() {
+                let product = a * b + carry;
+                // products with a zero aren't part of the table
+                let fact = (a != 0 && b != 0).then(|| {
+                    facts.push((a, b));
+                    facts.len() - 1
+                });
+                let column = row + i;
+                steps.push(Step {
+                    row,
+                    column,
+                    slot: Slot::Digit,
+                    expected: product % 10,
+                    fact,
+                });
+                carry = product / 10;
+                let is_last = i + 1 == top_digits.len();
+                if !is_last || carry != 0 {
+                    steps.push(Step {
+                        row,
+                        column: column + 1,
+                        slot: if is_last { Slot::Digit } else { Slot::Carry },
+                        expected: carry,
+                        fact,
+                    });
+                }
+            }
+        }
+        if bottom_digits.len() > 1 {
+            let row = bottom_digits.len();
+            steps.extend(
+                digits(top * bottom)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(column, expected)| Step {
+                        row,
+                        column,
+                        slot: Slot::Digit,
+                        expected,
+                        fact: None,
+                    }),
+            );
+        }
+        Self {
+            top,
+            bottom,
+            answers: vec![None; steps.len()],
+            recorded: vec![false; facts.len()],
+            steps,
+            facts,
+            correct: 0,
+            first_wrong: None,
+        }
+    }
+
+    /// Two digits times one or two digits, like `23 × 7` or `46 × 12`
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let bottom = if rng.gen_bool(0.5) {
+            rng.gen_range(2..=9)
+        } else {
+            rng.gen_range(11..=99)
+        };
+        Self::new(rng.gen_range(11..=99), bottom)
+    }
+
+    pub fn top(&self) -> u32 {
+        self.top
+    }
+
+    pub fn bottom(&self) -> u32 {
+        self.bottom
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Number of partial products, there is a final sum below them if it's more than one
+    pub fn partial_rows(&self) -> usize {
+        digits(self.bottom).len()
+    }
+
+    /// Number of columns the layout needs
+    pub fn width(&self) -> usize {
+        self.steps.iter().map(|s| s.column + 1).max().unwrap_or(0)
+    }
+
+    pub fn answer(&self, step: usize) -> Option<u32> {
+        self.answers[step]
+    }
+
+    /// Changes a cell, the checks of it and of every later step are dropped
+    pub fn set_answer(&mut self, step: usize, value: Option<u32>) {
+        self.answers[step] = value;
+        self.correct = self.correct.min(step);
+        self.first_wrong = None;
+    }
+
+    /// `Some(true)` for cells found correct by the last check, `Some(false)` for the first wrong one
+    pub fn checked(&self, step: usize) -> Option<bool> {
+        if step < self.correct {
+            Some(true)
+        } else if self.first_wrong == Some(step) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.correct == self.steps.len()
+    }
+
+    /// Checks the steps in order up to the first wrong or empty one.
+    /// Returns the table facts judged for the first time, with whether they were right
+    pub fn check(&mut self) -> Vec<(u32, u32, bool)> {
+        self.first_wrong = None;
+        self.correct = self.steps.len();
+        for (i, step) in self.steps.iter().enumerate() {
+            let answer = match (self.answers[i], step.slot) {
+                (None, Slot::Carry) => Some(0),
+                (answer, _) => answer,
+            };
+            match answer {
+                Some(a) if a == step.expected => continue,
+                Some(_) => self.first_wrong = Some(i),
+                // unfinished, nothing after it can be judged yet
+                None => (),
+            }
+            self.correct = i;
+            break;
+        }
+
+        let mut judged = vec![];
+        for (fact, &(a, b)) in self.facts.iter().enumerate() {
+            if self.recorded[fact] {
+                continue;
+            }
+            let mut cells = (0..self.steps.len()).filter(|&i| self.steps[i].fact == Some(fact));
+            let result = if cells.clone().any(|i| self.first_wrong == Some(i)) {
+                Some(false)
+            } else {
+                cells.all(|i| i < self.correct).then_some(true)
+            };
+            if let Some(correct) = result {
+                self.recorded[fact] = true;
+                judged.push((a, b, correct));
+            }
+        }
+        judged
+    }
+}
+
+/// Digits of `n` from the ones up
+fn digits(n: u32) -> Vec<u32> {
+    let mut digits = vec![n % 10];
+    let mut rest = n / 10;
+    while rest != 0 {
+        digits.push(rest % 10);
+        rest /= 10;
+    }
+    digits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(long: &mut LongMultiplication) {
+        for i in 0..long.steps().len() {
+            let expected = long.steps()[i].expected;
+            long.set_answer(i, Some(expected));
+        }
+    }
+
+    #[test]
+    fn lays_out_one_digit_products() {
+        let long = LongMultiplication::new(23, 7);
+        let steps: Vec<_> = long
+            .steps()
+            .iter()
+            .map(|s| (s.row(), s.column(), s.slot(), s.expected))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (0, 0, Slot::Digit, 1),
+                (0, 1, Slot::Carry, 2),
+                (0, 1, Slot::Digit, 6),
+                (0, 2, Slot::Digit, 1),
+            ]
+        );
+        assert_eq!(long.facts, [(3, 7), (2, 7)]);
+        assert_eq!((long.partial_rows(), long.width()), (1, 3));
+    }
+
+    #[test]
+    fn rows_add_up_to_the_product() {
+        for top in 11..=99 {
+            for bottom in 2..=99 {
+                let long = LongMultiplication::new(top, bottom);
+                let value = |row: usize| -> u32 {
+                    let digits = long
+                        .steps()
+                        .iter()
+                        .filter(|s| s.row() == row && s.slot() == Slot::Digit);
+                    digits
+                        .map(|s| s.expected * 10u32.pow(s.column() as u32))
+                        .sum()
+                };
+                let bottom_digits = digits(bottom);
+                for (row, b) in bottom_digits.iter().enumerate() {
+                    assert_eq!(
+                        value(row),
+                        top * b * 10u32.pow(row as u32),
+                        "{top} × {bottom}"
+                    );
+                }
+                if bottom_digits.len() > 1 {
+                    assert_eq!(value(bottom_digits.len()), top * bottom);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zero_digits_are_not_table_facts() {
+        assert_eq!(LongMultiplication::new(30, 7).facts, [(3, 7)]);
+    }
+
+    #[test]
+    fn judges_every_fact_once() {
+        let mut long = LongMultiplication::new(46, 12);
+        solve(&mut long);
+        let mut judged = long.check();
+        judged.sort();
+        assert_eq!(
+            judged,
+            [(4, 1, true), (4, 2, true), (6, 1, true), (6, 2, true)]
+        );
+        assert!(long.is_solved());
+        assert!(long.check().is_empty());
+    }
+
+    #[test]
+    fn stops_at_the_first_wrong_cell() {
+        let mut long = LongMultiplication::new(23, 7);
+        solve(&mut long);
+        long.set_answer(2, Some(5));
+        assert_eq!(long.check(), [(3, 7, true), (2, 7, false)]);
+        assert_eq!(long.checked(1), Some(true));
+        assert_eq!(long.checked(2), Some(false));
+        assert_eq!(long.checked(3), None);
+        assert!(!long.is_solved());
+
+        // a fixed cell doesn't count the fact again
+        long.set_answer(2, Some(6));
+        assert!(long.check().is_empty());
+        assert!(long.is_solved());
+    }
+
+    #[test]
+    fn empty_carries_count_as_zero() {
+        let mut long = LongMultiplication::new(11, 3);
+        solve(&mut long);
+        let carries = (0..long.steps().len()).filter(|&i| long.steps()[i].slot() == Slot::Carry);
+        for i in carries.collect::<Vec<_>>() {
+            long.set_answer(i, None);
+        }
+        long.check();
+        assert!(long.is_solved());
+    }
+}
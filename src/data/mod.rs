@@ -1,4 +1,19 @@
+pub mod backup;
+pub mod bundle;
+pub mod choices;
 pub(crate) mod consts;
+pub mod factors;
+pub mod leitner;
+pub mod long_mult;
+pub mod operation;
+pub(crate) mod persist;
+pub mod report;
+pub mod schedule;
 pub mod score;
+pub mod skip_count;
+pub mod store;
+pub mod time_limit;
 pub mod user;
 pub mod user_list;
+pub(crate) mod versioned;
+pub mod word_problems;
@@ -0,0 +1,145 @@
+use anyhow::{anyhow, Error};
+use serde::{Deserialize, Serialize};
+
+/// Basic operation whose facts are practiced.
+///
+/// Every operation keeps its facts as pairs of factors `(a, b)`, the inverse ones ask them
+/// backwards, `(a + b) − a` and `a·b ÷ a`, so all answers stay positive whole numbers
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Operation {
+    Addition,
+    Subtraction,
+    #[default]
+    Multiplication,
+    Division,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 4] = [
+        Operation::Addition,
+        Operation::Subtraction,
+        Operation::Multiplication,
+        Operation::Division,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Operation::Addition => "Addition",
+            Operation::Subtraction => "Subtraction",
+            Operation::Multiplication => "Multiplication",
+            Operation::Division => "Division",
+        }
+    }
+
+    pub fn sign(&self) -> char {
+        match self {
+            Operation::Addition => '+',
+            Operation::Subtraction => '−',
+            Operation::Multiplication => 'x',
+            Operation::Division => '÷',
+        }
+    }
+
+    /// Whether `(a, b)` and `(b, a)` are the same fact
+    pub fn is_commutative(&self) -> bool {
+        matches!(self, Operation::Addition | Operation::Multiplication)
+    }
+
+    pub fn table_title(&self) -> &'static str {
+        match self {
+            Operation::Addition => "Tabliczka dodawania",
+            Operation::Subtraction => "Tabliczka odejmowania",
+            Operation::Multiplication => "Tabliczka mnożenia",
+            Operation::Division => "Tabliczka dzielenia",
+        }
+    }
+
+    /// Numbers shown left of the sign and right of it for the fact `(a, b)`
+    pub fn operands(&self, a: u32, b: u32) -> (u32, u32) {
+        match self {
+            Operation::Addition | Operation::Multiplication => (a, b),
+            Operation::Subtraction => (a + b, a),
+            Operation::Division => (a * b, a),
+        }
+    }
+
+    pub fn answer(&self, a: u32, b: u32) -> u32 {
+        match self {
+            Operation::Addition => a + b,
+            Operation::Multiplication => a * b,
+            Operation::Subtraction | Operation::Division => b,
+        }
+    }
+
+    /// Number in the table cell of the fact `(a, b)`, the sum or the product it's built around
+    pub fn table_value(&self, a: usize, b: usize) -> usize {
+        match self {
+            Operation::Addition | Operation::Subtraction => a + b,
+            Operation::Multiplication | Operation::Division => a * b,
+        }
+    }
+}
+
+/// Which number of the equation is left blank for the answer.
+///
+/// Saved as a number, enum variants don't survive the `ron::Value` pass of the profile migrations
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum QuestionKind {
+    /// `7 × 8 = ?`
+    #[default]
+    Result,
+    /// `? × 8 = 56`
+    MissingFirst,
+    /// `7 × ? = 56`
+    MissingSecond,
+}
+
+impl QuestionKind {
+    pub const ALL: [QuestionKind; 3] = [
+        QuestionKind::Result,
+        QuestionKind::MissingFirst,
+        QuestionKind::MissingSecond,
+    ];
+
+    /// Expected answer for the fact `(a, b)` asked with `operation`
+    pub fn answer(&self, operation: Operation, a: u32, b: u32) -> u32 {
+        let (left, right) = operation.operands(a, b);
+        match self {
+            QuestionKind::Result => operation.answer(a, b),
+            QuestionKind::MissingFirst => left,
+            QuestionKind::MissingSecond => right,
+        }
+    }
+
+    /// Shape of the question, like `a x ? = c`
+    pub fn pattern(&self, operation: Operation) -> String {
+        let sign = operation.sign();
+        match self {
+            QuestionKind::Result => format!("a {sign} b = ?"),
+            QuestionKind::MissingFirst => format!("? {sign} b = c"),
+            QuestionKind::MissingSecond => format!("a {sign} ? = c"),
+        }
+    }
+}
+
+impl From<QuestionKind> for u8 {
+    fn from(kind: QuestionKind) -> Self {
+        match kind {
+            QuestionKind::Result => 0,
+            QuestionKind::MissingFirst => 1,
+            QuestionKind::MissingSecond => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for QuestionKind {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        QuestionKind::ALL
+            .get(value as usize)
+            .copied()
+            .ok_or_else(|| anyhow!("{value} isn't a question kind"))
+    }
+}
@@ -0,0 +1,74 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Result};
+use fs2::FileExt;
+
+const LOCK_FILE: &str = ".lock";
+
+/// Advisory lock on a data directory, held until dropped.
+/// Every write into the directory takes it, so two running instances never interleave
+pub(crate) struct DirLock(File);
+
+impl DirLock {
+    pub(crate) fn acquire(dir: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(dir.join(LOCK_FILE))?;
+        file.lock_exclusive()?;
+        Ok(Self(file))
+    }
+}
+
+impl Drop for DirLock {
+    fn drop(&mut self) {
+        let _ = self.0.unlock();
+    }
+}
+
+/// Writes `contents` next to `path`, syncs it to disk and renames it over `path`,
+/// so the target is either fully old or fully new, even if the app crashes midway.
+/// With `overwrite` set to false it fails if `path` already exists
+pub(crate) fn write_file(path: &Path, contents: &[u8], overwrite: bool) -> Result<()> {
+    let dir = parent_dir(path)?;
+    std::fs::create_dir_all(dir)?;
+    let _lock = DirLock::acquire(dir)?;
+    if !overwrite && path.exists() {
+        bail!("{} already exists", path.display());
+    }
+    write_atomically(path, contents)
+}
+
+/// Same as [`write_file`], but without taking the directory lock,
+/// for files outside of the app's data directory.
+/// The temporary file is named after the process, so instances writing the same file don't share it
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let dir = parent_dir(path)?;
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = PathBuf::from(tmp_path);
+    let mut tmp = File::create(&tmp_path)?;
+    tmp.write_all(contents)?;
+    tmp.sync_all()?;
+    drop(tmp);
+
+    std::fs::rename(&tmp_path, path)?;
+    // make the rename itself durable
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+fn parent_dir(path: &Path) -> Result<&Path> {
+    match path.parent() {
+        // relative file names live in the working directory
+        Some(dir) if dir.as_os_str().is_empty() => Ok(Path::new(".")),
+        Some(dir) => Ok(dir),
+        None => Err(anyhow!("{} has no parent directory", path.display())),
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::{
+    consts::export_dir,
+    operation::Operation,
+    persist::write_atomically,
+    score::MAX_PERCENT,
+    user::{ScoreWithEq, User},
+};
+
+/// File format of an exported score report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Csv { delimiter: u8 },
+    Json,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv { delimiter: b'\t' } => "tsv",
+            ReportFormat::Csv { .. } => "csv",
+            ReportFormat::Json => "json",
+        }
+    }
+}
+
+/// What goes into an exported report and in what format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportKind {
+    pub format: ReportFormat,
+    /// whether the answer history is written next to the summary
+    pub with_attempts: bool,
+}
+
+/// Summary of a single fact of the table
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FactRow {
+    pub operation: &'static str,
+    pub a: usize,
+    pub b: usize,
+    pub tries: u16,
+    pub correct: u16,
+    /// share of correct answers in percent, missing if the fact wasn't asked yet
+    pub percentage: Option<f32>,
+}
+
+/// One answer from a fact's history
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AttemptRow {
+    pub operation: &'static str,
+    pub a: usize,
+    pub b: usize,
+    /// shape of the question, like `? x b = c`
+    pub kind: String,
+    /// seconds since UNIX epoch
+    pub at: u64,
+    pub value: Option<u32>,
+    pub correct: bool,
+    pub time_ms: Option<u128>,
+    pub timed_out: bool,
+}
+
+/// Every fact kept for every operation, the ones outside of the profile's range included
+fn all_facts(user: &User) -> impl Iterator<Item = (Operation, ScoreWithEq)> + '_ {
+    Operation::ALL
+        .into_iter()
+        .flat_map(|operation| user.iter_grid(operation).map(move |s| (operation, s)))
+}
+
+pub fn fact_rows(user: &User) -> Vec<FactRow> {
+    all_facts(user)
+        .map(|(operation, s)| {
+            let (a, b) = s.factors();
+            let score = s.score();
+            FactRow {
+                operation: operation.label(),
+                a,
+                b,
+                tries: score.tries,
+                correct: score.correct,
+                percentage: score
+                    .get_percentage()
+                    .map(|p| p as f32 * 100.0 / MAX_PERCENT as f32),
+            }
+        })
+        .collect()
+}
+
+/// Long format of the report, one row per recorded answer
+pub fn attempt_rows(user: &User) -> Vec<AttemptRow> {
+    all_facts(user)
+        .flat_map(|(operation, s)| {
+            let (a, b) = s.factors();
+            s.score()
+                .history()
+                .iter()
+                .map(|attempt| AttemptRow {
+                    operation: operation.label(),
+                    a,
+                    b,
+                    kind: attempt.get_kind().pattern(operation),
+                    at: attempt
+                        .timestamp()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or_default(),
+                    value: attempt.value(),
+                    correct: attempt.is_correct(),
+                    time_ms: attempt.time_to_answer().map(|d| d.as_millis()),
+                    timed_out: attempt.is_timed_out(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Serializes `rows` in the given format
+pub fn to_bytes<T: Serialize>(rows: &[T], format: ReportFormat) -> Result<Vec<u8>> {
+    match format {
+        ReportFormat::Csv { delimiter } => {
+            let mut writer = csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .from_writer(vec![]);
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            Ok(writer.into_inner()?)
+        }
+        ReportFormat::Json => Ok(serde_json::to_vec_pretty(rows)?),
+    }
+}
+
+/// Where the per attempt report is written next to the summary at `path`
+pub fn attempts_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut attempts = path.with_file_name(format!("{stem}_attempts"));
+    if let Some(ext) = path.extension() {
+        attempts.set_extension(ext);
+    }
+    attempts
+}
+
+pub fn default_report_path(format: ReportFormat) -> PathBuf {
+    export_dir().join(format!("mult_table_scores.{}", format.extension()))
+}
+
+/// Writes the per fact summary of `user` to `path`.
+/// If the `kind` asks for it, the answer history is also written to [`attempts_path`], if there is any.
+/// Returns the path of the history file, if it was written
+pub fn export_report(user: &User, kind: ReportKind, path: &Path) -> Result<Option<PathBuf>> {
+    let ReportKind {
+        format,
+        with_attempts,
+    } = kind;
+    write_atomically(path, &to_bytes(&fact_rows(user), format)?)?;
+    if !with_attempts {
+        return Ok(None);
+    }
+    let attempts = attempt_rows(user);
+    if attempts.is_empty() {
+        return Ok(None);
+    }
+    let attempts_path = attempts_path(path);
+    write_atomically(&attempts_path, &to_bytes(&attempts, format)?)?;
+    Ok(Some(attempts_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{factors::Factors, operation::QuestionKind, score::Attempt};
+
+    #[test]
+    fn reports_every_operation_and_the_whole_grid() {
+        let mut user = User::new("A");
+        let fluency = user.fluency_threshold();
+        user.get_mut_score(Operation::Division, 6, 2)
+            .unwrap()
+            .record(
+                Attempt::new(Some(7), true, None).kind(QuestionKind::MissingFirst),
+                fluency,
+            );
+        user.set_factors(Factors::square(5));
+
+        let facts = fact_rows(&user);
+        let fact = facts
+            .iter()
+            .find(|r| r.operation == "Division" && (r.a, r.b) == (7, 3))
+            .unwrap();
+        assert_eq!((fact.tries, fact.correct), (1, 1));
+
+        let attempts = attempt_rows(&user);
+        assert_eq!(attempts.len(), 1);
+        let row = &attempts[0];
+        assert_eq!((row.operation, row.a, row.b), ("Division", 7, 3));
+        assert_eq!(
+            row.kind,
+            QuestionKind::MissingFirst.pattern(Operation::Division)
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    leitner,
+    score::{Attempt, Score, MAX_PERCENT},
+    user::ScoreWithEq,
+};
+
+const DAY: u64 = 24 * 60 * 60;
+/// Ease of facts answered for the first time, in thousandths
+const START_EASE: u16 = 2500;
+/// Intervals stop shrinking at this ease, however many mistakes are made
+const MIN_EASE: u16 = 1300;
+/// Weakness added to every fact when drawing at random, so the strong ones come back too
+const MIN_WEAKNESS: f64 = 0.1;
+/// Every this many days since a fact was last answered add its base weight once more
+const RECENCY_DAYS: f64 = 7.0;
+/// Facts not answered for longer weigh the same
+const MAX_RECENCY_DAYS: f64 = 28.0;
+/// Weight kept after every time a fact was already asked in the session
+const SESSION_DECAY: f64 = 0.5;
+
+/// When a fact should be asked again, scheduled the SM-2 way.
+///
+/// Every right answer stretches the interval by the fact's ease, a wrong one starts it over
+/// and lowers the ease, so the facts that keep being missed come back more often
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Review {
+    /// seconds since UNIX epoch of the answer the schedule comes from
+    reviewed: u64,
+    /// seconds since UNIX epoch
+    due: u64,
+    /// days between `reviewed` and `due`
+    interval: u32,
+    /// how much the interval grows after a right answer, in thousandths
+    ease: u16,
+    /// right answers in a row
+    streak: u16,
+}
+
+impl Review {
+    /// Schedule after `attempt`, `review` is the one it was asked with, `None` for a new fact.
+    /// Right answers given within the profile's `fluency` threshold count as easy
+    pub fn next(review: Option<Review>, attempt: &Attempt, fluency: Duration) -> Review {
+        let Review {
+            mut interval,
+            mut ease,
+            mut streak,
+            ..
+        } = review.unwrap_or(Review {
+            reviewed: 0,
+            due: 0,
+            interval: 0,
+            ease: START_EASE,
+            streak: 0,
+        });
+        // answer quality on SM-2's 0–5 scale
+        let quality = match (attempt.is_correct(), attempt.time_to_answer()) {
+            (false, _) => 1,
+            (true, Some(time)) if time <= fluency => 5,
+            (true, _) => 4,
+        };
+        if attempt.is_correct() {
+            interval = match streak {
+                0 => 1,
+                1 => 6,
+                _ => ((interval as u64 * ease as u64 + 500) / 1000).max(1) as u32,
+            };
+            streak = streak.saturating_add(1);
+        } else {
+            interval = 1;
+            streak = 0;
+        }
+        let miss = 5 - quality;
+        let change = 100 - miss * (80 + miss * 20);
+        ease = (ease as i32 + change).max(MIN_EASE as i32) as u16;
+
+        let reviewed = attempt
+            .timestamp()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Review {
+            reviewed,
+            due: reviewed + interval as u64 * DAY,
+            interval,
+            ease,
+            streak,
+        }
+    }
+
+    /// Schedule built from answers logged before schedules were kept
+    pub fn replay(history: &[Attempt], fluency: Duration) -> Option<Review> {
+        history.iter().fold(None, |review, attempt| {
+            Some(Review::next(review, attempt, fluency))
+        })
+    }
+
+    /// The one of two schedules of the same fact made after the later answer
+    pub fn latest(a: Option<Review>, b: Option<Review>) -> Option<Review> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(if b.reviewed > a.reviewed { b } else { a }),
+            (a, b) => a.or(b),
+        }
+    }
+
+    pub fn due(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.due)
+    }
+
+    pub fn interval_days(&self) -> u32 {
+        self.interval
+    }
+
+    pub fn is_due(&self, now: SystemTime) -> bool {
+        self.due() <= now
+    }
+}
+
+/// Order in which facts are drawn for a test, lower goes first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// time it was due at, the longest overdue ones lead
+    Due(SystemTime),
+    /// never answered
+    New,
+    /// time it will be due at
+    Later(SystemTime),
+}
+
+/// How the facts of a test are picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// due facts of the SM-2 schedule first, then new ones
+    #[default]
+    SpacedRepetition,
+    /// due facts of the Leitner boxes first, then new ones
+    Leitner,
+    /// random facts, the weak ones, the ones not seen for a while and the ones
+    /// not asked yet in the session being more likely
+    Weighted,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 3] = [
+        Strategy::SpacedRepetition,
+        Strategy::Leitner,
+        Strategy::Weighted,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Strategy::SpacedRepetition => "Due facts first",
+            Strategy::Leitner => "Leitner boxes",
+            Strategy::Weighted => "Weighted random",
+        }
+    }
+
+    /// Facts in the order they're asked. `shown` tells how many times the fact `(a, b)`
+    /// was already asked in this session
+    pub fn order(
+        &self,
+        mut scores: Vec<ScoreWithEq>,
+        shown: impl Fn((usize, usize)) -> u32,
+        rng: &mut impl Rng,
+    ) -> Vec<ScoreWithEq> {
+        let now = SystemTime::now();
+        let priority = match self {
+            Strategy::SpacedRepetition => Score::priority,
+            Strategy::Leitner => leitner::priority,
+            Strategy::Weighted => {
+                // weighted sampling without replacement, every fact gets a random key
+                // that tends to be bigger the heavier it is
+                let mut keyed: Vec<_> = scores
+                    .into_iter()
+                    .map(|s| {
+                        let weight = weight(s.score(), shown(s.factors()), now);
+                        (rng.gen::<f64>().powf(1.0 / weight), s)
+                    })
+                    .collect();
+                keyed.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+                return keyed.into_iter().map(|(_, s)| s).collect();
+            }
+        };
+        scores.shuffle(rng);
+        // the worst scores lead where the schedule ties
+        scores.sort_by(|a, b| {
+            priority(a.score(), now)
+                .cmp(&priority(b.score(), now))
+                .then_with(|| a.cmp(b))
+        });
+        scores
+    }
+}
+
+/// How likely the fact is drawn by [`Strategy::Weighted`], relative to the other ones
+fn weight(score: &Score, shown: u32, now: SystemTime) -> f64 {
+    let weakness = score
+        .get_percentage()
+        .map_or(1.0, |p| 1.0 - p as f64 / MAX_PERCENT as f64);
+    let days = score
+        .history()
+        .last()
+        .and_then(|a| now.duration_since(a.timestamp()).ok())
+        .map_or(MAX_RECENCY_DAYS, |d| {
+            (d.as_secs_f64() / DAY as f64).min(MAX_RECENCY_DAYS)
+        });
+    (MIN_WEAKNESS + weakness) * (1.0 + days / RECENCY_DAYS) * SESSION_DECAY.powi(shown as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLUENCY: Duration = Duration::from_secs(3);
+
+    fn answer(correct: bool, secs: u64) -> Attempt {
+        Attempt::new(None, correct, Some(Duration::from_secs(secs)))
+    }
+
+    fn after(answers: &[Attempt]) -> Review {
+        Review::replay(answers, FLUENCY).unwrap()
+    }
+
+    #[test]
+    fn right_answers_stretch_the_interval() {
+        let fast = answer(true, 1);
+        let first = Review::next(None, &fast, FLUENCY);
+        assert_eq!((first.interval, first.ease, first.streak), (1, 2600, 1));
+        assert_eq!(first.due, first.reviewed + DAY);
+
+        let third = after(&[fast, fast, fast]);
+        assert_eq!((third.interval, third.ease, third.streak), (16, 2800, 3));
+    }
+
+    #[test]
+    fn slow_answers_keep_the_ease() {
+        let review = after(&[answer(true, 5), answer(true, 5)]);
+        assert_eq!((review.interval, review.ease), (6, START_EASE));
+    }
+
+    #[test]
+    fn answers_within_the_threshold_are_fast() {
+        let at = |secs| Review::next(None, &answer(true, secs), FLUENCY).ease;
+        assert_eq!(at(3), 2600);
+        assert_eq!(at(4), START_EASE);
+        let untimed = Review::next(None, &Attempt::new(None, true, None), FLUENCY);
+        assert_eq!(untimed.ease, START_EASE);
+    }
+
+    #[test]
+    fn wrong_answers_start_over() {
+        let fast = answer(true, 1);
+        let wrong = answer(false, 1);
+        let review = after(&[fast, fast, fast, wrong]);
+        assert_eq!((review.interval, review.ease, review.streak), (1, 2260, 0));
+
+        let review = after(&[wrong; 5]);
+        assert_eq!(review.ease, MIN_EASE);
+        assert!(Review::replay(&[], FLUENCY).is_none());
+    }
+
+    #[test]
+    fn latest_comes_from_the_later_answer() {
+        let earlier = Review::next(None, &answer(true, 1), FLUENCY);
+        let later = Review {
+            reviewed: earlier.reviewed + 1,
+            ..earlier
+        };
+        assert_eq!(Review::latest(Some(earlier), Some(later)), Some(later));
+        assert_eq!(Review::latest(Some(later), Some(earlier)), Some(later));
+        assert_eq!(Review::latest(None, Some(earlier)), Some(earlier));
+    }
+}
@@ -1,11 +1,95 @@
-use serde::{Deserialize, Serialize};
-use std::fmt::Display;
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Serialize,
+};
+use std::{
+    fmt::Display,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+use super::{
+    operation::QuestionKind,
+    schedule::{Priority, Review},
+};
+
+/// Single answer given for a fact, kept in the append-only log of a [`Score`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Attempt {
+    /// seconds since UNIX epoch
+    at: u64,
+    value: Option<u32>,
+    correct: bool,
+    time_ms: Option<u32>,
+    /// answers logged before question kinds existed were all results
+    #[serde(default)]
+    kind: QuestionKind,
+    /// no answer was given before the time of a timed set ran out
+    #[serde(default)]
+    timed_out: bool,
+}
+
+impl Attempt {
+    pub fn new(value: Option<u32>, correct: bool, time_to_answer: Option<Duration>) -> Self {
+        Self {
+            at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            value,
+            correct,
+            time_ms: time_to_answer.map(|d| d.as_millis().min(u32::MAX as u128) as u32),
+            kind: QuestionKind::Result,
+            timed_out: false,
+        }
+    }
+
+    /// Records the answer as given to a question of `kind`
+    pub fn kind(mut self, kind: QuestionKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn get_kind(&self) -> QuestionKind {
+        self.kind
+    }
+
+    /// Records the question as left unanswered when the time ran out, rather than answered wrong
+    pub fn timed_out(mut self, timed_out: bool) -> Self {
+        self.timed_out = timed_out;
+        self
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    pub fn timestamp(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.at)
+    }
+
+    pub fn value(&self) -> Option<u32> {
+        self.value
+    }
+
+    pub fn is_correct(&self) -> bool {
+        self.correct
+    }
+
+    pub fn time_to_answer(&self) -> Option<Duration> {
+        self.time_ms.map(|ms| Duration::from_millis(ms as u64))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Score {
     pub(crate) tries: u16,
     pub(crate) correct: u16,
     pub(crate) percentage: Option<u16>,
+    /// counts recorded before the attempt log existed
+    carried: Sdto,
+    history: Vec<Attempt>,
+    /// when the fact should be asked again, `None` until it's answered
+    review: Option<Review>,
 }
 pub const MAX_PERCENT: u16 = 10_000;
 impl Score {
@@ -15,36 +99,150 @@ impl Score {
 
     fn calc_percentage(correct: u16, tries: u16) -> Option<u16> {
         if tries != 0 {
-            Some(correct * MAX_PERCENT / tries)
+            Some((correct as u32 * MAX_PERCENT as u32 / tries as u32) as u16)
         } else {
             None
         }
     }
 
-    pub fn update(&mut self, is_correct: bool) {
-        if is_correct {
-            self.correct += 1;
-        }
-        self.tries += 1;
+    fn from_log(carried: Sdto, history: Vec<Attempt>, review: Option<Review>) -> Self {
+        let mut score = Self {
+            tries: 0,
+            correct: 0,
+            percentage: None,
+            carried,
+            history,
+            review,
+        };
+        score.recalculate();
+        score
+    }
+
+    fn recalculate(&mut self) {
+        let logged_correct = self.history.iter().filter(|a| a.correct).count() as u16;
+        self.tries = self.carried.0.saturating_add(self.history.len() as u16);
+        self.correct = self.carried.1.saturating_add(logged_correct);
         self.percentage = Self::calc_percentage(self.correct, self.tries);
     }
 
-    pub fn new(tries: u16, correct: u16) -> Self {
-        Self {
-            tries,
-            correct,
-            percentage: Self::calc_percentage(correct, tries),
+    /// Logs `attempt`, right answers given within `fluency` push the next review further
+    pub fn record(&mut self, attempt: Attempt, fluency: Duration) {
+        self.review = Some(Review::next(self.review, &attempt, fluency));
+        self.history.push(attempt);
+        self.recalculate();
+    }
+
+    /// Adds the answers of another copy of the same fact, answers present in both are kept once
+    pub fn merge(&mut self, other: &Score) {
+        self.carried = Sdto(
+            self.carried.0.max(other.carried.0),
+            self.carried.1.max(other.carried.1),
+        );
+        for attempt in &other.history {
+            if !self.history.contains(attempt) {
+                self.history.push(*attempt);
+            }
         }
+        self.history.sort_by_key(|a| a.at);
+        self.review = Review::latest(self.review, other.review);
+        self.recalculate();
+    }
+
+    /// Score of two facts counted as one, unlike [`Score::merge`] answers aren't deduplicated
+    pub fn combined(&self, other: &Score) -> Score {
+        let carried = Sdto(
+            self.carried.0.saturating_add(other.carried.0),
+            self.carried.1.saturating_add(other.carried.1),
+        );
+        let mut history: Vec<_> = self.history.iter().chain(&other.history).copied().collect();
+        history.sort_by_key(|a| a.at);
+        Self::from_log(carried, history, Review::latest(self.review, other.review))
+    }
+
+    pub fn history(&self) -> &[Attempt] {
+        &self.history
+    }
+
+    /// Tries and correct answers logged for questions of `kind`
+    pub fn kind_counts(&self, kind: QuestionKind) -> (u16, u16) {
+        self.history
+            .iter()
+            .filter(|a| a.kind == kind)
+            .fold((0, 0), |(tries, correct), a| {
+                (tries + 1, correct + a.correct as u16)
+            })
+    }
+
+    /// Logged questions the time ran out on, they count as wrong answers too
+    pub fn time_outs(&self) -> u16 {
+        self.history.iter().filter(|a| a.timed_out).count() as u16
+    }
+
+    pub fn review(&self) -> Option<&Review> {
+        self.review.as_ref()
+    }
+
+    /// Works out the schedule of a log written before schedules were kept,
+    /// with the `fluency` threshold of the profile it belongs to
+    pub(crate) fn replay_review(&mut self, fluency: Duration) {
+        if self.review.is_none() {
+            self.review = Review::replay(&self.history, fluency);
+        }
+    }
+
+    /// Where the fact goes when drawing a test at `now`.
+    /// Facts only counted before the answer log existed have no schedule and are due right away
+    pub fn priority(&self, now: SystemTime) -> Priority {
+        match &self.review {
+            Some(review) if review.is_due(now) => Priority::Due(review.due()),
+            Some(review) => Priority::Later(review.due()),
+            None if self.tries == 0 => Priority::New,
+            None => Priority::Due(UNIX_EPOCH),
+        }
+    }
+
+    /// Middle one of the logged answer times, answers without a time are left out
+    pub fn median_time(&self) -> Option<Duration> {
+        let mut times: Vec<_> = self
+            .history
+            .iter()
+            .filter_map(Attempt::time_to_answer)
+            .collect();
+        if times.is_empty() {
+            return None;
+        }
+        times.sort();
+        let mid = times.len() / 2;
+        Some(if times.len() % 2 == 0 {
+            (times[mid - 1] + times[mid]) / 2
+        } else {
+            times[mid]
+        })
+    }
+
+    /// Share of the logged answers that were right and given within `threshold`,
+    /// in the units of [`MAX_PERCENT`]. Answers counted before the log existed have no time and are left out
+    pub fn fluency(&self, threshold: Duration) -> Option<u16> {
+        let fluent = self
+            .history
+            .iter()
+            .filter(|a| a.correct && a.time_to_answer().is_some_and(|t| t <= threshold))
+            .count();
+        Self::calc_percentage(fluent as u16, self.history.len() as u16)
+    }
+
+    pub fn last_correct(&self) -> Option<&Attempt> {
+        self.history.iter().rev().find(|a| a.correct)
+    }
+
+    pub fn new(tries: u16, correct: u16) -> Self {
+        Self::from_log(Sdto(tries, correct), vec![], None)
     }
 }
 
 impl From<&Sdto> for Score {
     fn from(value: &Sdto) -> Self {
-        Self {
-            percentage: Self::calc_percentage(value.1, value.0),
-            tries: value.0,
-            correct: value.1,
-        }
+        Self::new(value.0, value.1)
     }
 }
 
@@ -60,13 +258,40 @@ impl From<&Score> for Sdto {
     }
 }
 
+struct ScoreVisitor;
+
+impl<'de> Visitor<'de> for ScoreVisitor {
+    type Value = Score;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a (tries, correct) pair optionally followed by an attempt log and a schedule")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let tries = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let correct = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        // files written before the log existed only hold the `Sdto` pair
+        let history: Vec<Attempt> = seq.next_element()?.unwrap_or_default();
+        // and the ones written before schedules were kept have it replayed once the profile is read,
+        // see `User::loaded`
+        let review = seq.next_element()?.flatten();
+        Ok(Score::from_log(Sdto(tries, correct), history, review))
+    }
+}
+
 impl<'de> Deserialize<'de> for Score {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let s = Sdto::deserialize(deserializer)?;
-        Ok(s.into())
+        deserializer.deserialize_tuple_struct("Sdto", 4, ScoreVisitor)
     }
 }
 
@@ -75,7 +300,7 @@ impl Serialize for Score {
     where
         S: serde::Serializer,
     {
-        Sdto::serialize(&self.into(), serializer)
+        ScoreDto(self.carried.0, self.carried.1, &self.history, self.review).serialize(serializer)
     }
 }
 
@@ -108,9 +333,15 @@ impl Display for Score {
         if let Some(p) = self.percentage {
             s = format!("{s}\n percent correct: {}%", p as f32 / 100.0);
         }
+        if let Some(v) = self.history.last().and_then(Attempt::value) {
+            s = format!("{s}\nlast answer: {v}");
+        }
         write!(f, "{s}")
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, Eq, PartialEq)]
 struct Sdto(u16, u16);
+
+#[derive(Serialize)]
+struct ScoreDto<'a>(u16, u16, &'a [Attempt], Option<Review>);
@@ -0,0 +1,53 @@
+use std::ops::RangeInclusive;
+
+use rand::{seq::index::sample, Rng};
+
+/// A row of the multiplication table counted in steps of its factor, `7, 14, 21, …`,
+/// with some of the numbers left blank
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkipCount {
+    row: u32,
+    columns: Vec<u32>,
+    /// indexes into `columns` of the blanks, in the order they're answered
+    blanks: Vec<usize>,
+}
+
+impl SkipCount {
+    /// Counts `row` over `columns`, blanking about half of the numbers.
+    /// The first one stays shown when there are more, it gives away the step
+    pub fn new(row: u32, columns: RangeInclusive<u32>, rng: &mut impl Rng) -> Self {
+        let columns: Vec<_> = columns.collect();
+        let shown = usize::from(columns.len() > 1);
+        let candidates = columns.len() - shown;
+        let mut blanks: Vec<_> = sample(rng, candidates, (columns.len() / 2).clamp(1, candidates))
+            .into_iter()
+            .map(|i| i + shown)
+            .collect();
+        blanks.sort_unstable();
+        Self {
+            row,
+            columns,
+            blanks,
+        }
+    }
+
+    /// Second factor of the facts, the label of the row in the table
+    pub fn row(&self) -> u32 {
+        self.row
+    }
+
+    /// First factors of the facts, in counting order
+    pub fn columns(&self) -> &[u32] {
+        &self.columns
+    }
+
+    /// Index of the blank at `column`, `None` if the number there is shown
+    pub fn blank_at(&self, column: usize) -> Option<usize> {
+        self.blanks.iter().position(|&b| b == column)
+    }
+
+    /// Facts `(a, b)` of the blanks, in the order they're answered
+    pub fn facts(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.blanks.iter().map(|&i| (self.columns[i], self.row))
+    }
+}
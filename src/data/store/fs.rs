@@ -0,0 +1,339 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Result};
+
+use super::ProfileStore;
+use crate::data::{
+    consts::app_dir,
+    persist::{write_file, DirLock},
+    user::User,
+    user_list::{UserList, USER_LIST_FILE},
+    versioned::{from_versioned_bytes, quarantine, to_versioned_string, NewerVersion, Versioned},
+};
+
+pub(crate) const ARCHIVE_DIR: &str = "archive";
+/// Where [`FsStore::replace_all`] writes the new files before moving them in
+const STAGING_DIR: &str = ".replacing";
+/// Where [`FsStore::replace_all`] moves the replaced files until the new ones are in
+const REPLACED_DIR: &str = ".replaced";
+
+/// One `<name>.ron` file per profile plus `UserList.ron`, all in one directory.
+/// Archived profiles are moved to its `archive` subdirectory
+#[derive(Debug, Clone)]
+pub struct FsStore {
+    root: PathBuf,
+    read_only: bool,
+}
+
+impl FsStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            read_only: false,
+        }
+    }
+
+    /// Store that never changes `root`: writes fail and broken files are reported
+    /// without being quarantined
+    pub fn read_only(root: impl Into<PathBuf>) -> Self {
+        Self {
+            read_only: true,
+            ..Self::new(root)
+        }
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.read_only {
+            bail!("{} is opened read-only", self.root.display());
+        }
+        Ok(())
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// File of the profile `name`, names [`UserList::check_name`] refuses have none
+    pub fn file_path(&self, name: &str) -> Result<PathBuf> {
+        Ok(self.root.join(file_name(name)?))
+    }
+
+    fn user_list_path(&self) -> PathBuf {
+        self.root.join(format!("{USER_LIST_FILE}.ron"))
+    }
+
+    fn archived_path(&self, name: &str) -> Result<PathBuf> {
+        Ok(self.root.join(ARCHIVE_DIR).join(file_name(name)?))
+    }
+
+    /// Renames under the directory lock, refusing to replace an existing file
+    fn move_file(&self, from: &Path, to: &Path) -> Result<()> {
+        self.check_writable()?;
+        std::fs::create_dir_all(&self.root)?;
+        let _lock = DirLock::acquire(&self.root)?;
+        if to.exists() {
+            bail!("{} already exists", to.display());
+        }
+        if let Some(dir) = to.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::rename(from, to)?;
+        Ok(())
+    }
+
+    /// Profile files, the user list and the archive in `dir`, everything `replace_all` swaps
+    fn data_entries(dir: &Path) -> Result<Vec<String>> {
+        let mut names = vec![];
+        for entry in std::fs::read_dir(dir)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if name == ARCHIVE_DIR || name.ends_with(".ron") {
+                names.push(name);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Moves the data of `self.root` to `replaced` and the one of `staged` in its place,
+    /// moving everything back if a rename fails
+    fn swap_in(&self, staged: &Path, replaced: &Path) -> Result<()> {
+        let mut moved_out = vec![];
+        let mut moved_in = vec![];
+        let swapped = (|| -> Result<()> {
+            for name in Self::data_entries(&self.root)? {
+                std::fs::rename(self.root.join(&name), replaced.join(&name))?;
+                moved_out.push(name);
+            }
+            for name in Self::data_entries(staged)? {
+                std::fs::rename(staged.join(&name), self.root.join(&name))?;
+                moved_in.push(name);
+            }
+            Ok(())
+        })();
+        if swapped.is_err() {
+            for name in moved_in {
+                let _ = std::fs::rename(self.root.join(&name), staged.join(&name));
+            }
+            for name in moved_out {
+                let _ = std::fs::rename(replaced.join(&name), self.root.join(&name));
+            }
+        }
+        swapped
+    }
+
+    fn list_in(dir: &Path) -> Result<Vec<String>> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        let mut names = vec![];
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("ron") {
+                continue;
+            }
+            match path.file_stem().and_then(|s| s.to_str()) {
+                Some(name) if name != USER_LIST_FILE => names.push(name.to_owned()),
+                _ => (),
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn load<T: Versioned>(&self, path: &Path) -> Result<Option<T>> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        match from_versioned_bytes(&bytes) {
+            Ok(data) => Ok(Some(data)),
+            // the file is fine, it only has to wait for the app to be updated
+            Err(e) if e.is::<NewerVersion>() => {
+                Err(e.context(format!("{} can't be opened", path.display())))
+            }
+            Err(e) if self.read_only => Err(e.context(format!("{} is broken", path.display()))),
+            Err(e) => Err(quarantine(&self.root, path, e)),
+        }
+    }
+}
+
+fn file_name(name: &str) -> Result<String> {
+    UserList::check_name(name)?;
+    Ok(format!("{name}.ron"))
+}
+
+impl Default for FsStore {
+    fn default() -> Self {
+        Self::new(app_dir())
+    }
+}
+
+impl ProfileStore for FsStore {
+    fn load_user_list(&self) -> Result<Option<UserList>> {
+        self.load(&self.user_list_path())
+    }
+
+    fn save_user_list(&self, list: &UserList) -> Result<()> {
+        self.check_writable()?;
+        write_file(
+            &self.user_list_path(),
+            to_versioned_string(list)?.as_bytes(),
+            true,
+        )
+    }
+
+    fn load_profile(&self, name: &str) -> Result<Option<User>> {
+        self.load(&self.file_path(name)?)
+    }
+
+    fn create_profile(&self, user: &User) -> Result<()> {
+        self.check_writable()?;
+        if self.archived_path(user.name())?.exists() {
+            bail!("profile {} is archived", user.name());
+        }
+        write_file(
+            &self.file_path(user.name())?,
+            to_versioned_string(user)?.as_bytes(),
+            false,
+        )
+    }
+
+    fn save_profile(&self, user: &User) -> Result<()> {
+        self.check_writable()?;
+        write_file(
+            &self.file_path(user.name())?,
+            to_versioned_string(user)?.as_bytes(),
+            true,
+        )
+    }
+
+    fn rename_profile(&self, from: &str, to: &str) -> Result<()> {
+        self.move_file(&self.file_path(from)?, &self.file_path(to)?)
+    }
+
+    fn delete_profile(&self, name: &str) -> Result<()> {
+        self.check_writable()?;
+        std::fs::create_dir_all(&self.root)?;
+        let _lock = DirLock::acquire(&self.root)?;
+        let path = self.file_path(name)?;
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        } else {
+            std::fs::remove_file(self.archived_path(name)?)?;
+        }
+        Ok(())
+    }
+
+    fn list_profiles(&self) -> Result<Vec<String>> {
+        Self::list_in(&self.root)
+    }
+
+    fn archive_profile(&self, name: &str) -> Result<()> {
+        self.move_file(&self.file_path(name)?, &self.archived_path(name)?)
+    }
+
+    fn restore_profile(&self, name: &str) -> Result<()> {
+        self.move_file(&self.archived_path(name)?, &self.file_path(name)?)
+    }
+
+    fn list_archived_profiles(&self) -> Result<Vec<String>> {
+        Self::list_in(&self.root.join(ARCHIVE_DIR))
+    }
+
+    fn load_archived_profile(&self, name: &str) -> Result<Option<User>> {
+        self.load(&self.archived_path(name)?)
+    }
+
+    /// Writes the new files next to the current ones first, then swaps them in
+    /// under the directory lock
+    fn replace_all(&self, active: &[User], archived: &[User], list: &UserList) -> Result<()> {
+        self.check_writable()?;
+        let staged = self.root.join(STAGING_DIR);
+        let replaced = self.root.join(REPLACED_DIR);
+        let _ = std::fs::remove_dir_all(&staged);
+        let staging = FsStore::new(&staged);
+        let written = (|| -> Result<()> {
+            for user in active.iter().chain(archived) {
+                staging.create_profile(user)?;
+            }
+            for user in archived {
+                staging.archive_profile(user.name())?;
+            }
+            staging.save_user_list(list)
+        })();
+        let swapped = written.and_then(|_| {
+            let _lock = DirLock::acquire(&self.root)?;
+            let _ = std::fs::remove_dir_all(&replaced);
+            std::fs::create_dir_all(&replaced)?;
+            self.swap_in(&staged, &replaced)
+        });
+        let _ = std::fs::remove_dir_all(&staged);
+        // a failed swap may have left some of the old files there
+        if swapped.is_ok() {
+            let _ = std::fs::remove_dir_all(&replaced);
+        }
+        swapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_path_stays_in_the_root() {
+        let store = FsStore::new("root");
+        assert_eq!(
+            store.file_path("Ann").unwrap(),
+            Path::new("root").join("Ann.ron")
+        );
+        for name in ["../escaped", "a/b", "a\\b", "..", "UserList"] {
+            assert!(store.file_path(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn replaces_every_profile() {
+        let root = std::env::temp_dir().join(format!("mult_table_replace_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let store = FsStore::new(&root);
+        store.create_profile(&User::new("C")).unwrap();
+        store.create_profile(&User::new("D")).unwrap();
+        store.archive_profile("D").unwrap();
+
+        let list = UserList::from_profiles(vec!["A".into()], vec!["B".into()]);
+        store
+            .replace_all(&[User::new("A")], &[User::new("B")], &list)
+            .unwrap();
+        assert_eq!(store.list_profiles().unwrap(), ["A"]);
+        assert_eq!(store.list_archived_profiles().unwrap(), ["B"]);
+        assert!(store.load_user_list().unwrap().is_some());
+        assert!(!root.join(STAGING_DIR).exists() && !root.join(REPLACED_DIR).exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn keeps_files_of_newer_versions() {
+        let root = std::env::temp_dir().join(format!("mult_table_newer_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let store = FsStore::new(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let path = store.file_path("A").unwrap();
+        std::fs::write(&path, "(version:1000,data:())").unwrap();
+
+        let e = store.load_profile("A").unwrap_err();
+        assert!(e.is::<NewerVersion>());
+        assert!(path.exists());
+        std::fs::write(&path, "not a profile").unwrap();
+        assert!(FsStore::read_only(&root).load_profile("A").is_err());
+        assert!(path.exists());
+        assert!(store.load_profile("A").is_err());
+        assert!(!path.exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,188 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use anyhow::{anyhow, bail, Result};
+
+use super::ProfileStore;
+use crate::data::{user::User, user_list::UserList};
+
+/// Keeps everything in memory, nothing survives the process
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    user_list: Mutex<Option<UserList>>,
+    profiles: Mutex<BTreeMap<String, User>>,
+    archived: Mutex<BTreeMap<String, User>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the profile into the archived map or back.
+    /// Both directions lock `profiles` before `archived`, like `create_profile`, so they can't deadlock
+    fn move_profile(&self, name: &str, archive: bool) -> Result<()> {
+        let mut profiles = self.profiles.lock().map_err(poisoned)?;
+        let mut archived = self.archived.lock().map_err(poisoned)?;
+        let (from, to) = if archive {
+            (&mut *profiles, &mut *archived)
+        } else {
+            (&mut *archived, &mut *profiles)
+        };
+        move_between(from, to, name)
+    }
+}
+
+fn poisoned<T>(_: T) -> anyhow::Error {
+    anyhow!("memory store lock poisoned")
+}
+
+impl ProfileStore for MemoryStore {
+    fn load_user_list(&self) -> Result<Option<UserList>> {
+        Ok(self.user_list.lock().map_err(poisoned)?.clone())
+    }
+
+    fn save_user_list(&self, list: &UserList) -> Result<()> {
+        *self.user_list.lock().map_err(poisoned)? = Some(list.clone());
+        Ok(())
+    }
+
+    fn load_profile(&self, name: &str) -> Result<Option<User>> {
+        Ok(self.profiles.lock().map_err(poisoned)?.get(name).cloned())
+    }
+
+    fn create_profile(&self, user: &User) -> Result<()> {
+        let mut profiles = self.profiles.lock().map_err(poisoned)?;
+        if profiles.contains_key(user.name())
+            || self
+                .archived
+                .lock()
+                .map_err(poisoned)?
+                .contains_key(user.name())
+        {
+            bail!("profile {} already exists", user.name());
+        }
+        profiles.insert(user.name().to_owned(), user.clone());
+        Ok(())
+    }
+
+    fn save_profile(&self, user: &User) -> Result<()> {
+        self.profiles
+            .lock()
+            .map_err(poisoned)?
+            .insert(user.name().to_owned(), user.clone());
+        Ok(())
+    }
+
+    fn rename_profile(&self, from: &str, to: &str) -> Result<()> {
+        let mut profiles = self.profiles.lock().map_err(poisoned)?;
+        if profiles.contains_key(to) {
+            bail!("profile {to} already exists");
+        }
+        let user = profiles
+            .remove(from)
+            .ok_or_else(|| anyhow!("profile {from} doesn't exist"))?;
+        profiles.insert(to.to_owned(), user);
+        Ok(())
+    }
+
+    fn delete_profile(&self, name: &str) -> Result<()> {
+        if self
+            .profiles
+            .lock()
+            .map_err(poisoned)?
+            .remove(name)
+            .is_some()
+        {
+            return Ok(());
+        }
+        self.archived
+            .lock()
+            .map_err(poisoned)?
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| anyhow!("profile {name} doesn't exist"))
+    }
+
+    fn list_profiles(&self) -> Result<Vec<String>> {
+        Ok(self
+            .profiles
+            .lock()
+            .map_err(poisoned)?
+            .keys()
+            .cloned()
+            .collect())
+    }
+
+    fn archive_profile(&self, name: &str) -> Result<()> {
+        self.move_profile(name, true)
+    }
+
+    fn restore_profile(&self, name: &str) -> Result<()> {
+        self.move_profile(name, false)
+    }
+
+    fn list_archived_profiles(&self) -> Result<Vec<String>> {
+        Ok(self
+            .archived
+            .lock()
+            .map_err(poisoned)?
+            .keys()
+            .cloned()
+            .collect())
+    }
+
+    fn load_archived_profile(&self, name: &str) -> Result<Option<User>> {
+        Ok(self.archived.lock().map_err(poisoned)?.get(name).cloned())
+    }
+
+    fn replace_all(&self, active: &[User], archived: &[User], list: &UserList) -> Result<()> {
+        let by_name = |users: &[User]| -> BTreeMap<_, _> {
+            users
+                .iter()
+                .map(|u| (u.name().to_owned(), u.clone()))
+                .collect()
+        };
+        let mut profiles = self.profiles.lock().map_err(poisoned)?;
+        let mut archived_profiles = self.archived.lock().map_err(poisoned)?;
+        let mut user_list = self.user_list.lock().map_err(poisoned)?;
+        *profiles = by_name(active);
+        *archived_profiles = by_name(archived);
+        *user_list = Some(list.clone());
+        Ok(())
+    }
+}
+
+fn move_between(
+    from: &mut BTreeMap<String, User>,
+    to: &mut BTreeMap<String, User>,
+    name: &str,
+) -> Result<()> {
+    if to.contains_key(name) {
+        bail!("profile {name} already exists");
+    }
+    let user = from
+        .remove(name)
+        .ok_or_else(|| anyhow!("profile {name} doesn't exist"))?;
+    to.insert(name.to_owned(), user);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deleting_an_active_profile_keeps_the_archived_one() {
+        let store = MemoryStore::new();
+        store.create_profile(&User::new("A")).unwrap();
+        store.archive_profile("A").unwrap();
+        store.save_profile(&User::new("A")).unwrap();
+
+        store.delete_profile("A").unwrap();
+        assert!(store.list_profiles().unwrap().is_empty());
+        assert_eq!(store.list_archived_profiles().unwrap(), ["A"]);
+        store.delete_profile("A").unwrap();
+        assert!(store.list_archived_profiles().unwrap().is_empty());
+        assert!(store.delete_profile("A").is_err());
+    }
+}
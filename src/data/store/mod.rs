@@ -0,0 +1,57 @@
+use std::{fmt::Debug, sync::Arc};
+
+use anyhow::Result;
+
+use super::{user::User, user_list::UserList};
+
+mod fs;
+mod memory;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use fs::FsStore;
+pub use memory::MemoryStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::{SqliteStore, DB_FILE};
+
+/// Place where profiles and the user list are kept.
+///
+/// Methods block, use [`run`] to call them from async code
+pub trait ProfileStore: Send + Sync + Debug {
+    /// `Ok(None)` if no list was saved yet
+    fn load_user_list(&self) -> Result<Option<UserList>>;
+    fn save_user_list(&self, list: &UserList) -> Result<()>;
+
+    /// `Ok(None)` if there is no profile with that name
+    fn load_profile(&self, name: &str) -> Result<Option<User>>;
+    /// Fails instead of replacing an existing profile
+    fn create_profile(&self, user: &User) -> Result<()>;
+    fn save_profile(&self, user: &User) -> Result<()>;
+    fn rename_profile(&self, from: &str, to: &str) -> Result<()>;
+    /// Removes the profile whether it is active or archived
+    fn delete_profile(&self, name: &str) -> Result<()>;
+    /// Lists active profiles only
+    fn list_profiles(&self) -> Result<Vec<String>>;
+
+    /// Keeps the profile, but hides it from loading and listing until restored
+    fn archive_profile(&self, name: &str) -> Result<()>;
+    fn restore_profile(&self, name: &str) -> Result<()>;
+    fn list_archived_profiles(&self) -> Result<Vec<String>>;
+    /// `Ok(None)` if there is no archived profile with that name
+    fn load_archived_profile(&self, name: &str) -> Result<Option<User>>;
+
+    /// Replaces every profile and the user list as one change,
+    /// if it fails the store is left as it was
+    fn replace_all(&self, active: &[User], archived: &[User], list: &UserList) -> Result<()>;
+}
+
+pub type Store = Arc<dyn ProfileStore>;
+
+/// Runs blocking store operations off the async executor
+pub async fn run<T: Send + 'static>(
+    store: &Store,
+    op: impl FnOnce(&dyn ProfileStore) -> Result<T> + Send + 'static,
+) -> Result<T> {
+    let store = Arc::clone(store);
+    tokio::task::spawn_blocking(move || op(store.as_ref())).await?
+}
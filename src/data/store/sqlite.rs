@@ -0,0 +1,525 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, bail, Result};
+use rusqlite::{backup::Backup, params, Connection, OpenFlags, OptionalExtension, Transaction};
+
+use super::{FsStore, ProfileStore};
+use crate::data::{
+    operation::Operation,
+    score::{Attempt, MAX_PERCENT},
+    user::User,
+    user_list::UserList,
+    versioned::{from_versioned_bytes, to_versioned_string},
+};
+
+pub const DB_FILE: &str = "profiles.sqlite3";
+
+const SCHEMA: &str = "
+    PRAGMA foreign_keys = ON;
+    CREATE TABLE IF NOT EXISTS user_list (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS profiles (
+        name TEXT PRIMARY KEY,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS attempts (
+        profile TEXT NOT NULL REFERENCES profiles(name) ON UPDATE CASCADE ON DELETE CASCADE,
+        a INTEGER NOT NULL,
+        b INTEGER NOT NULL,
+        at INTEGER NOT NULL,
+        value INTEGER,
+        correct INTEGER NOT NULL,
+        time_ms INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS attempts_by_fact ON attempts (profile, a, b, at);
+";
+
+/// `MIGRATIONS[i]` upgrades a database with `user_version` `i` to `i + 1`
+const MIGRATIONS: &[&str] = &[
+    // 0 -> 1: archived profiles
+    "ALTER TABLE profiles ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;",
+    // 1 -> 2: questions the time ran out on
+    "ALTER TABLE attempts ADD COLUMN timed_out INTEGER NOT NULL DEFAULT 0;",
+    // 2 -> 3: attempts of every operation and question kind
+    "ALTER TABLE attempts ADD COLUMN operation TEXT NOT NULL DEFAULT 'Multiplication';
+     ALTER TABLE attempts ADD COLUMN kind INTEGER NOT NULL DEFAULT 0;",
+];
+
+/// Answer logs already in the `attempts` table per fact
+type Written<'a> = HashMap<(Operation, (usize, usize)), &'a [Attempt]>;
+
+/// Keeps profiles in a single SQLite database.
+///
+/// Profiles are stored whole, in the same versioned format as [`FsStore`] files,
+/// and every logged answer is also kept as a row of the `attempts` table for querying
+#[derive(Debug)]
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    /// Opens an in-memory copy of the database at `path`, which is never written to,
+    /// migrations and later changes only apply to the copy
+    pub fn open_copy(path: impl AsRef<Path>) -> Result<Self> {
+        let from = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut conn = Connection::open_in_memory()?;
+        copy_pages(&from, &mut conn)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            conn.execute_batch(migration)?;
+            conn.pragma_update(None, "user_version", i + 1)?;
+        }
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Opens the database in `dir`, importing the `.ron` files found there when it is created
+    pub fn open_in(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(DB_FILE);
+        let is_new = !path.exists();
+        let store = Self::open(path)?;
+        if is_new {
+            store.import(&FsStore::new(dir))?;
+        }
+        Ok(store)
+    }
+
+    /// Copies every readable profile, active or archived, and the user list from `from`,
+    /// skipping profiles that already exist in the database
+    pub fn import(&self, from: &dyn ProfileStore) -> Result<()> {
+        let active = from.list_profiles()?.into_iter().map(|name| (name, false));
+        let archived = from
+            .list_archived_profiles()?
+            .into_iter()
+            .map(|name| (name, true));
+        for (name, archived) in active.chain(archived) {
+            let loaded = if archived {
+                from.load_archived_profile(&name)
+            } else {
+                from.load_profile(&name)
+            };
+            let user = match loaded {
+                Ok(Some(user)) => user,
+                Ok(None) => continue,
+                Err(e) => {
+                    println!("skipped importing {name}: {e:#}");
+                    continue;
+                }
+            };
+            let mut conn = self.conn()?;
+            let tx = conn.transaction()?;
+            if !Self::exists(&tx, &name)? {
+                Self::insert_profile(&tx, &user, archived)?;
+                tx.commit()?;
+            }
+        }
+        if let Some(list) = from.load_user_list()? {
+            self.save_user_list(&list)?;
+        }
+        Ok(())
+    }
+
+    /// Share of correct answers since `since` for facts of `operation` with `factor` as the first number,
+    /// in the same units as [`crate::data::score::Score::get_percentage`]
+    pub fn row_accuracy(
+        &self,
+        profile: &str,
+        operation: Operation,
+        factor: u32,
+        since: SystemTime,
+    ) -> Result<Option<u16>> {
+        let since = since.duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let (tries, correct): (i64, Option<i64>) = self.conn()?.query_row(
+            "SELECT COUNT(*), SUM(correct) FROM attempts
+             WHERE profile = ?1 AND operation = ?2 AND a = ?3 AND at >= ?4",
+            params![profile, operation.label(), factor, since],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok((tries != 0).then(|| (correct.unwrap_or(0) * MAX_PERCENT as i64 / tries) as u16))
+    }
+
+    /// Copies the database at `from` into a new file at `to` with SQLite's backup API,
+    /// which sees a consistent state including writes still in the WAL
+    pub fn copy_database(from: &Path, to: &Path) -> Result<()> {
+        let from = Connection::open_with_flags(from, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        copy_pages(&from, &mut Connection::open(to)?)
+    }
+
+    fn conn(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| anyhow!("sqlite store lock poisoned"))
+    }
+
+    /// Writes the answer log of every fact into the `attempts` table.
+    /// Given the `previous` version of the profile, whose log is already there, only the answers
+    /// logged since are added. A fact whose log changed in another way, like by a merge, is written again whole
+    fn write_attempts(tx: &Transaction, user: &User, previous: Option<&User>) -> Result<()> {
+        let written: Written = match previous {
+            Some(previous) => Operation::ALL
+                .into_iter()
+                .flat_map(|operation| {
+                    previous
+                        .cells(operation)
+                        .map(move |(fact, score)| ((operation, fact), score.history()))
+                })
+                .collect(),
+            None => {
+                tx.execute(
+                    "DELETE FROM attempts WHERE profile = ?1",
+                    params![user.name()],
+                )?;
+                HashMap::new()
+            }
+        };
+        let mut insert = tx.prepare(
+            "INSERT INTO attempts
+                 (profile, operation, a, b, at, value, correct, time_ms, timed_out, kind)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+        let mut delete = tx.prepare(
+            "DELETE FROM attempts WHERE profile = ?1 AND operation = ?2 AND a = ?3 AND b = ?4",
+        )?;
+        // the whole grid, answers outside of the current range are still history
+        let cells = Operation::ALL
+            .into_iter()
+            .flat_map(|operation| user.cells(operation).map(move |cell| (operation, cell)));
+        for (operation, ((a, b), score)) in cells {
+            let history = score.history();
+            let old = written
+                .get(&(operation, (a, b)))
+                .copied()
+                .unwrap_or_default();
+            // the log only grows at its end, unless answers from another copy were merged in
+            let new = if let Some(new) = history.strip_prefix(old) {
+                new
+            } else {
+                delete.execute(params![user.name(), operation.label(), a, b])?;
+                history
+            };
+            for attempt in new {
+                insert.execute(params![
+                    user.name(),
+                    operation.label(),
+                    a,
+                    b,
+                    seconds(attempt),
+                    attempt.value(),
+                    attempt.is_correct(),
+                    attempt.time_to_answer().map(|t| t.as_millis() as i64),
+                    attempt.is_timed_out(),
+                    u8::from(attempt.get_kind()),
+                ])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn exists(tx: &Transaction, name: &str) -> Result<bool> {
+        Ok(tx
+            .query_row(
+                "SELECT 1 FROM profiles WHERE name = ?1",
+                params![name],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some())
+    }
+
+    /// Adds a profile that isn't in the database yet, with all of its attempts
+    fn insert_profile(tx: &Transaction, user: &User, archived: bool) -> Result<()> {
+        tx.execute(
+            "INSERT INTO profiles (name, data, archived) VALUES (?1, ?2, ?3)",
+            params![user.name(), to_versioned_string(user)?, archived],
+        )?;
+        Self::write_attempts(tx, user, None)
+    }
+
+    fn load_row(&self, name: &str, archived: bool) -> Result<Option<User>> {
+        let data: Option<String> = self
+            .conn()?
+            .query_row(
+                "SELECT data FROM profiles WHERE name = ?1 AND archived = ?2",
+                params![name, archived],
+                |row| row.get(0),
+            )
+            .optional()?;
+        data.map(|d| from_versioned_bytes(d.as_bytes())).transpose()
+    }
+
+    fn set_archived(&self, name: &str, archived: bool) -> Result<()> {
+        let changed = self.conn()?.execute(
+            "UPDATE profiles SET archived = ?2 WHERE name = ?1 AND archived = ?3",
+            params![name, archived, !archived],
+        )?;
+        if changed == 0 {
+            bail!("profile {name} doesn't exist");
+        }
+        Ok(())
+    }
+
+    fn upsert_profile(&self, user: &User, overwrite: bool) -> Result<()> {
+        let data = to_versioned_string(user)?;
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        let old: Option<String> = tx
+            .query_row(
+                "SELECT data FROM profiles WHERE name = ?1",
+                params![user.name()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if old.is_some() && !overwrite {
+            bail!("profile {} already exists", user.name());
+        }
+        // a stored profile that can't be read anymore has its attempts written again whole
+        let previous = old.and_then(|d| from_versioned_bytes::<User>(d.as_bytes()).ok());
+        tx.execute(
+            "INSERT INTO profiles (name, data) VALUES (?1, ?2)
+             ON CONFLICT (name) DO UPDATE SET data = excluded.data",
+            params![user.name(), data],
+        )?;
+        Self::write_attempts(&tx, user, previous.as_ref())?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Time of the attempt as stored in the `attempts` table
+fn seconds(attempt: &Attempt) -> i64 {
+    attempt
+        .timestamp()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn copy_pages(from: &Connection, to: &mut Connection) -> Result<()> {
+    // all pages in one step, so a write in between can't make it start over
+    Backup::new(from, to)?.run_to_completion(i32::MAX, Duration::from_millis(10), None)?;
+    Ok(())
+}
+
+impl ProfileStore for SqliteStore {
+    fn load_user_list(&self) -> Result<Option<UserList>> {
+        let data: Option<String> = self
+            .conn()?
+            .query_row("SELECT data FROM user_list WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        data.map(|d| from_versioned_bytes(d.as_bytes())).transpose()
+    }
+
+    fn save_user_list(&self, list: &UserList) -> Result<()> {
+        self.conn()?.execute(
+            "INSERT INTO user_list (id, data) VALUES (0, ?1)
+             ON CONFLICT (id) DO UPDATE SET data = excluded.data",
+            params![to_versioned_string(list)?],
+        )?;
+        Ok(())
+    }
+
+    fn load_profile(&self, name: &str) -> Result<Option<User>> {
+        self.load_row(name, false)
+    }
+
+    fn create_profile(&self, user: &User) -> Result<()> {
+        self.upsert_profile(user, false)
+    }
+
+    fn save_profile(&self, user: &User) -> Result<()> {
+        self.upsert_profile(user, true)
+    }
+
+    fn rename_profile(&self, from: &str, to: &str) -> Result<()> {
+        let changed = self.conn()?.execute(
+            "UPDATE profiles SET name = ?2 WHERE name = ?1",
+            params![from, to],
+        )?;
+        if changed == 0 {
+            bail!("profile {from} doesn't exist");
+        }
+        Ok(())
+    }
+
+    fn delete_profile(&self, name: &str) -> Result<()> {
+        let changed = self
+            .conn()?
+            .execute("DELETE FROM profiles WHERE name = ?1", params![name])?;
+        if changed == 0 {
+            bail!("profile {name} doesn't exist");
+        }
+        Ok(())
+    }
+
+    fn list_profiles(&self) -> Result<Vec<String>> {
+        let conn = self.conn()?;
+        let mut stmt =
+            conn.prepare("SELECT name FROM profiles WHERE archived = 0 ORDER BY name")?;
+        let names = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(names)
+    }
+
+    fn archive_profile(&self, name: &str) -> Result<()> {
+        self.set_archived(name, true)
+    }
+
+    fn restore_profile(&self, name: &str) -> Result<()> {
+        self.set_archived(name, false)
+    }
+
+    fn list_archived_profiles(&self) -> Result<Vec<String>> {
+        let conn = self.conn()?;
+        let mut stmt =
+            conn.prepare("SELECT name FROM profiles WHERE archived = 1 ORDER BY name")?;
+        let names = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(names)
+    }
+
+    fn load_archived_profile(&self, name: &str) -> Result<Option<User>> {
+        self.load_row(name, true)
+    }
+
+    fn replace_all(&self, active: &[User], archived: &[User], list: &UserList) -> Result<()> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        // attempts go with their profiles
+        tx.execute("DELETE FROM profiles", [])?;
+        for user in active {
+            Self::insert_profile(&tx, user, false)?;
+        }
+        for user in archived {
+            Self::insert_profile(&tx, user, true)?;
+        }
+        tx.execute(
+            "INSERT INTO user_list (id, data) VALUES (0, ?1)
+             ON CONFLICT (id) DO UPDATE SET data = excluded.data",
+            params![to_versioned_string(list)?],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{factors::Factors, score::Attempt, store::MemoryStore};
+
+    #[test]
+    fn adds_only_new_attempts_on_save() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let mut user = User::new("A");
+        let fluency = user.fluency_threshold();
+        let rows = |store: &SqliteStore| -> Vec<(i64, i64)> {
+            let conn = store.conn().unwrap();
+            let mut stmt = conn
+                .prepare("SELECT rowid, at FROM attempts ORDER BY at, rowid")
+                .unwrap();
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)));
+            rows.unwrap().collect::<Result<_, _>>().unwrap()
+        };
+        let record = |user: &mut User, attempt| {
+            let score = user.get_mut_score(Operation::Multiplication, 2, 3).unwrap();
+            score.record(attempt, fluency);
+        };
+        record(&mut user, Attempt::new(Some(12), true, None));
+        store.create_profile(&user).unwrap();
+        let first = rows(&store);
+
+        record(&mut user, Attempt::new(Some(11), false, None));
+        store.save_profile(&user).unwrap();
+        let second = rows(&store);
+        assert_eq!(second.len(), 2);
+        assert_eq!(second[0], first[0]);
+
+        // an older answer merged in rewrites the fact
+        let mut other = User::new("A");
+        let older = ron::from_str("(at:0,value:Some(12),correct:true,time_ms:None)").unwrap();
+        other
+            .get_mut_score(Operation::Multiplication, 2, 3)
+            .unwrap()
+            .record(older, fluency);
+        user.merge(&other);
+        store.save_profile(&user).unwrap();
+        let merged = rows(&store);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].1, 0);
+    }
+
+    #[test]
+    fn failed_replace_changes_nothing() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        store.create_profile(&User::new("C")).unwrap();
+        let list = UserList::from_profiles(vec!["A".into()], vec!["A".into()]);
+        let a = [User::new("A")];
+        assert!(store.replace_all(&a, &a, &list).is_err());
+        assert_eq!(store.list_profiles().unwrap(), ["C"]);
+        assert!(store.load_user_list().unwrap().is_none());
+    }
+
+    #[test]
+    fn imports_archived_profiles() {
+        let from = MemoryStore::new();
+        for name in ["A", "B"] {
+            from.create_profile(&User::new(name)).unwrap();
+        }
+        from.archive_profile("B").unwrap();
+
+        let store = SqliteStore::open_in_memory().unwrap();
+        store.import(&from).unwrap();
+        assert_eq!(store.list_profiles().unwrap(), ["A"]);
+        assert_eq!(store.list_archived_profiles().unwrap(), ["B"]);
+        store.restore_profile("B").unwrap();
+        assert!(store.load_profile("B").unwrap().is_some());
+    }
+
+    #[test]
+    fn keeps_attempts_outside_of_the_range_and_of_every_operation() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let mut user = User::new("A");
+        store.create_profile(&user).unwrap();
+        let fluency = user.fluency_threshold();
+        for operation in [Operation::Multiplication, Operation::Division] {
+            let score = user.get_mut_score(operation, 6, 2).unwrap();
+            score.record(Attempt::new(Some(21), true, None), fluency);
+        }
+        user.set_factors(Factors::square(5));
+        store.save_profile(&user).unwrap();
+
+        for operation in [Operation::Multiplication, Operation::Division] {
+            let accuracy = store.row_accuracy("A", operation, 7, UNIX_EPOCH).unwrap();
+            assert_eq!(accuracy, Some(MAX_PERCENT));
+        }
+        let accuracy = store.row_accuracy("A", Operation::Addition, 7, UNIX_EPOCH);
+        assert_eq!(accuracy.unwrap(), None);
+    }
+}
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+/// How long a set of equations may take in the timed mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeLimit {
+    /// the whole set, however many equations it has
+    PerSet(Duration),
+    /// every equation of the set, all of them share the sum of their times
+    PerQuestion(Duration),
+}
+
+impl TimeLimit {
+    /// Time given to a set of `questions` equations
+    pub fn for_set(&self, questions: usize) -> Duration {
+        match *self {
+            TimeLimit::PerSet(limit) => limit,
+            TimeLimit::PerQuestion(limit) => limit * questions as u32,
+        }
+    }
+}
+
+/// Time left shown as `m:ss`, rounded up so the countdown reaches `0:00` only when it runs out
+pub fn countdown(left: Duration) -> String {
+    let secs = left.as_secs() + (left.subsec_nanos() != 0) as u64;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
@@ -1,50 +1,201 @@
-use anyhow::{anyhow, Error};
+use anyhow::{anyhow, bail, Error};
+use ron::{
+    value::{Map, Number},
+    Value,
+};
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, sync::Arc};
-use tokio::io::AsyncWriteExt;
+use std::{sync::Arc, time::Duration};
 
-use crate::helpers::{get_file_path, load_file, make_nxn_mat};
+use crate::helpers::make_nxn_mat;
 
 use super::{
-    consts::{app_dir, CELL_N},
+    consts::{DEFAULT_FLUENCY_MS, DEFAULT_SIZE},
+    factors::Factors,
+    operation::Operation,
     score::Score,
+    store::{run, ProfileStore, Store},
+    user_list::UserList,
+    versioned::{unchanged, Migration, Versioned},
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct User {
     name: String,
-    scores: [[Arc<Score>; CELL_N]; CELL_N],
+    /// part of the table that is shown and practiced
+    factors: Factors,
+    /// square grid covering at least `factors`, cells outside of them keep the scores
+    /// of a range that was practiced before
+    scores: Vec<Vec<Arc<Score>>>,
+    /// same grid for [`Operation::Division`], the fact `(a, b)` is asked as `a·b ÷ a`
+    division: Vec<Vec<Arc<Score>>>,
+    addition: Vec<Vec<Arc<Score>>>,
+    /// the fact `(a, b)` is asked as `(a + b) − a`
+    subtraction: Vec<Vec<Arc<Score>>>,
+    /// counts `(a, b)` and `(b, a)` of commutative operations as one fact.
+    /// Answers are still kept in the cell that was asked, so the setting can be turned off again
+    commutative: bool,
+    /// right answers given within this many milliseconds count as fluent
+    fluency_ms: u32,
 }
 
 impl User {
     pub fn new(name: &str) -> Self {
-        let scores = make_nxn_mat();
         User {
             name: String::from(name),
-            scores,
+            factors: Factors::square(DEFAULT_SIZE),
+            scores: make_nxn_mat(DEFAULT_SIZE),
+            division: make_nxn_mat(DEFAULT_SIZE),
+            addition: make_nxn_mat(DEFAULT_SIZE),
+            subtraction: make_nxn_mat(DEFAULT_SIZE),
+            commutative: false,
+            fluency_ms: DEFAULT_FLUENCY_MS,
         }
     }
-    pub fn iter(&self) -> ScoresIter {
+
+    pub fn factors(&self) -> &Factors {
+        &self.factors
+    }
+
+    /// Changes the table range, scores of the facts outside of it are kept for later
+    pub fn set_factors(&mut self, factors: Factors) {
+        self.grow(factors.max());
+        self.factors = factors;
+    }
+
+    pub fn is_commutative(&self) -> bool {
+        self.commutative
+    }
+
+    pub fn set_commutative(&mut self, commutative: bool) {
+        self.commutative = commutative;
+    }
+
+    /// Longest time a right answer may take to count as fluent
+    pub fn fluency_threshold(&self) -> Duration {
+        Duration::from_millis(self.fluency_ms as u64)
+    }
+
+    pub fn set_fluency_threshold(&mut self, threshold: Duration) {
+        self.fluency_ms = threshold.as_millis().min(u32::MAX as u128) as u32;
+    }
+
+    /// Whether `(a, b)` of `operation` shares its score with `(b, a)`
+    pub fn merges_mirrored(&self, operation: Operation) -> bool {
+        self.commutative && operation.is_commutative()
+    }
+
+    /// Makes sure the grid covers every fact up to `size` × `size`
+    pub fn grow(&mut self, size: usize) {
+        for grid in [
+            &mut self.scores,
+            &mut self.division,
+            &mut self.addition,
+            &mut self.subtraction,
+        ] {
+            if size <= grid.len() {
+                continue;
+            }
+            for row in grid.iter_mut() {
+                row.resize_with(size, Default::default);
+            }
+            grid.resize_with(size, || (0..size).map(|_| Default::default()).collect());
+        }
+    }
+
+    fn grid(&self, operation: Operation) -> &Vec<Vec<Arc<Score>>> {
+        match operation {
+            Operation::Addition => &self.addition,
+            Operation::Subtraction => &self.subtraction,
+            Operation::Multiplication => &self.scores,
+            Operation::Division => &self.division,
+        }
+    }
+
+    fn grid_mut(&mut self, operation: Operation) -> &mut Vec<Vec<Arc<Score>>> {
+        match operation {
+            Operation::Addition => &mut self.addition,
+            Operation::Subtraction => &mut self.subtraction,
+            Operation::Multiplication => &mut self.scores,
+            Operation::Division => &mut self.division,
+        }
+    }
+
+    /// Multiplication scores of the profile's range
+    pub fn iter(&self) -> ScoresIter<'_> {
+        self.iter_in(Operation::Multiplication, &self.factors)
+    }
+
+    /// Every fact kept for `operation`, including the ones outside of the profile's range
+    pub fn iter_grid(&self, operation: Operation) -> ScoresIter<'_> {
+        self.iter_in(operation, &Factors::square(self.grid(operation).len()))
+    }
+
+    /// Every cell of `operation`'s grid with the fact it was asked as, mirrored facts aren't combined
+    pub fn cells(&self, operation: Operation) -> impl Iterator<Item = ((usize, usize), &Score)> {
+        self.grid(operation)
+            .iter()
+            .enumerate()
+            .flat_map(|(x, col)| {
+                col.iter()
+                    .enumerate()
+                    .map(move |(y, score)| ((x + 1, y + 1), score.as_ref()))
+            })
+    }
+
+    /// Iterates the facts of `factors`, which have to fit in the grid, see [`User::grow`]
+    pub fn iter_in(&self, operation: Operation, factors: &Factors) -> ScoresIter<'_> {
         ScoresIter {
-            index_x: 0,
-            index_y: 0,
+            index_x: *factors.cols().start(),
+            index_y: *factors.rows().start(),
+            factors: factors.clone(),
+            operation,
             user: self,
         }
     }
 
-    pub fn get_user_path(name: &str) -> PathBuf {
-        get_file_path(name)
+    /// Score of the fact, shared with the mirrored one if [`User::merges_mirrored`]
+    pub fn get_score(&self, operation: Operation, x: usize, y: usize) -> Arc<Score> {
+        self.get_opt_score(operation, x, y)
+            .unwrap_or_else(|| panic!("{x}, {y} is outside of the score grid"))
     }
-    pub fn get_score(&self, x: usize, y: usize) -> Arc<Score> {
-        self.scores[x][y].clone()
+
+    /// Score of the single cell that was asked, answers are recorded here.
+    /// A score still shared with an older copy of the profile is copied first, `None` is only outside of the grid
+    pub fn get_mut_score(
+        &mut self,
+        operation: Operation,
+        x: usize,
+        y: usize,
+    ) -> Option<&mut Score> {
+        Some(Arc::make_mut(
+            self.grid_mut(operation).get_mut(x)?.get_mut(y)?,
+        ))
     }
 
-    pub fn get_mut_score(&mut self, x: usize, y: usize) -> Option<&mut Score> {
-        Arc::<Score>::get_mut(&mut self.scores[x][y])
+    pub fn get_opt_score(&self, operation: Operation, x: usize, y: usize) -> Option<Arc<Score>> {
+        let grid = self.grid(operation);
+        let score = grid.get(x)?.get(y)?;
+        if x == y || !self.merges_mirrored(operation) {
+            return Some(score.clone());
+        }
+        let mirrored = grid.get(y)?.get(x)?;
+        Some(Arc::new(score.combined(mirrored)))
     }
 
-    pub fn get_opt_score(&self, x: usize, y: usize) -> Option<Arc<Score>> {
-        Some(self.scores.get(x)?.get(y)?.clone())
+    /// Adds the scores of another copy of this profile, e.g. one exported from a different computer
+    pub fn merge(&mut self, other: &User) {
+        self.grow(other.scores.len());
+        for operation in Operation::ALL {
+            let grids = self
+                .grid_mut(operation)
+                .iter_mut()
+                .zip(other.grid(operation));
+            for (row, other_row) in grids {
+                for (score, other_score) in row.iter_mut().zip(other_row) {
+                    Arc::make_mut(score).merge(other_score);
+                }
+            }
+        }
     }
 
     pub fn name(&self) -> &str {
@@ -55,70 +206,234 @@ impl User {
         std::mem::replace(&mut self.name, new_name)
     }
 
-    async fn load_from_file_anyhow(name: &str) -> Result<Self, Arc<Error>> {
-        async {
-            Ok(ron::de::from_bytes(
-                &load_file(Self::get_user_path(name)).await?,
-            )?)
-        }
+    /// Loads the profile, creating it if it doesn't exist yet.
+    /// Unreadable profiles are moved aside by the store instead of being replaced
+    pub async fn load_user(store: Store, name: String) -> Result<Box<Self>, Arc<Error>> {
+        let loaded = run(&store, {
+            let name = name.clone();
+            move |s| s.load_profile(&name)
+        })
         .await
-        .map_err(Arc::new)
+        .map_err(Arc::new)?;
+        match loaded {
+            Some(u) => Ok(Box::new(u)),
+            None => {
+                println!("user not found");
+                Self::create_new(store, &name).await.map(Box::new)
+            }
+        }
     }
-    pub async fn load_user(name: String) -> Box<Self> {
-        let loaded = Self::load_from_file_anyhow(&name).await;
-        Box::new(if let Ok(u) = loaded {
-            u
-        } else {
-            println!("user not found");
-            Self::create_new(&name).await.unwrap()
+
+    /// Renames the profile in the store, the name saved inside it and its entry in `list` as one unit.
+    /// Steps that already finished are undone if a later one fails
+    pub async fn rename(
+        &self,
+        store: Store,
+        list: &UserList,
+        new_name: String,
+    ) -> Result<(Box<Self>, UserList), Arc<Error>> {
+        let mut renamed = self.clone();
+        let old_name = renamed.set_name(new_name.clone());
+        let mut new_list = list.clone();
+        new_list
+            .rename_user(&old_name, &new_name)
+            .map_err(Arc::new)?;
+        let original = self.clone();
+        run(&store, move |s| {
+            rename_in_store(s, &original, &renamed, &new_list)
+                .map_err(|e| anyhow!("couldn't rename {old_name} to {new_name}: {e:#}"))?;
+            Ok((Box::new(renamed), new_list))
         })
+        .await
+        .map_err(Arc::new)
     }
 
-    pub async fn rename_user_file(&self, new_name: String) -> Result<String, Arc<anyhow::Error>> {
-        let mut from = app_dir();
-        let mut to = from.clone();
-        from.push(&self.name);
-        to.push(&new_name);
+    pub async fn create_new(store: Store, name: &str) -> Result<Self, Arc<Error>> {
+        let new_user = Self::new(name);
+        let to_save = new_user.clone();
+        run(&store, move |s| s.create_profile(&to_save))
+            .await
+            .map_err(Arc::new)?;
+        println!("created {name} profile!");
+        Ok(new_user)
+    }
 
-        tokio::fs::rename(from, to)
+    pub async fn update_file(&self, store: Store) -> Result<(), Arc<Error>> {
+        let to_save = self.clone();
+        run(&store, move |s| s.save_profile(&to_save))
             .await
-            .map_err(|e| Arc::new(anyhow!(e)))?;
-        Ok(new_name)
+            .map_err(Arc::new)
     }
+}
 
-    pub async fn create_new(name: &str) -> Result<Self, Arc<Error>> {
-        let new_user = Self::new(name);
-        async {
-            let mut file = tokio::fs::File::create(Self::get_user_path(name)).await?;
-            println!("created {name}.ron file!");
-            file.write_all(ron::ser::to_string(&new_user)?.as_bytes())
-                .await?;
-            Ok(())
-        }
-        .await
-        .map_err(Arc::new)?;
-        Ok(new_user)
+fn rename_in_store(
+    store: &dyn ProfileStore,
+    original: &User,
+    renamed: &User,
+    list: &UserList,
+) -> Result<(), Error> {
+    let (from, to) = (original.name(), renamed.name());
+    store.rename_profile(from, to)?;
+    if let Err(e) = store.save_profile(renamed) {
+        return Err(with_rollback(e, store.rename_profile(to, from)));
+    }
+    if let Err(e) = store.save_user_list(list) {
+        let rollback = store
+            .save_profile(original)
+            .and_then(|_| store.delete_profile(to));
+        return Err(with_rollback(e, rollback));
     }
+    Ok(())
+}
+
+fn with_rollback(error: Error, rollback: Result<(), Error>) -> Error {
+    match rollback {
+        Ok(()) => error,
+        Err(r) => anyhow!("{error:#}, and undoing the finished steps failed too: {r:#}"),
+    }
+}
 
-    pub async fn update_file(&self) -> Result<(), Arc<Error>> {
-        async {
-            let name = &self.name;
-            let mut file = tokio::fs::File::options()
-                .write(true)
-                .open(Self::get_user_path(name))
-                .await?;
-            file.write_all(ron::ser::to_string(self)?.as_bytes())
-                .await?;
-            Ok(())
+impl Versioned for User {
+    const MIGRATIONS: &'static [Migration] = &[
+        // 0 -> 1: profile wrapped in a versioned envelope
+        unchanged,
+        // 1 -> 2: table size stored next to the scores, which may be any square size now
+        add_size,
+        // 2 -> 3: the size became a rectangle of factor ranges
+        size_to_factors,
+        // 3 -> 4: separate scores for division
+        add_division,
+        // 4 -> 5: addition and subtraction scores
+        add_addition_and_subtraction,
+        // 5 -> 6: commutative facts setting
+        add_commutative,
+        // 6 -> 7: answer speed that counts as fluent
+        add_fluency_threshold,
+    ];
+
+    /// Scores written before schedules were kept get one replayed from their answer log,
+    /// fast answers are the ones within the profile's own threshold
+    fn loaded(mut self) -> Self {
+        let fluency = self.fluency_threshold();
+        for operation in Operation::ALL {
+            for score in self.grid_mut(operation).iter_mut().flatten() {
+                Arc::make_mut(score).replay_review(fluency);
+            }
         }
-        .await
-        .map_err(Arc::new)
+        self
     }
 }
 
+/// Profiles from before the size setting always held a 10×10 table
+fn add_size(value: Value) -> Result<Value, Error> {
+    let Value::Map(mut map) = value else {
+        bail!("profile isn't a struct");
+    };
+    let size = map
+        .iter()
+        .find_map(|(key, value)| match (key, value) {
+            (Value::String(key), Value::Seq(rows)) if key == "scores" => Some(rows.len()),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("profile has no scores"))?;
+    map.insert(
+        Value::String("size".into()),
+        Value::Number(Number::from(size as u64)),
+    );
+    Ok(Value::Map(map))
+}
+
+fn size_to_factors(value: Value) -> Result<Value, Error> {
+    let Value::Map(mut map) = value else {
+        bail!("profile isn't a struct");
+    };
+    let size = map
+        .remove(&Value::String("size".into()))
+        .ok_or_else(|| anyhow!("profile has no size"))?;
+    let range = |size: Value| {
+        let fields = [("start", Value::Number(Number::from(1))), ("end", size)];
+        Value::Map(Map::from_iter(
+            fields.map(|(k, v)| (Value::String(k.into()), v)),
+        ))
+    };
+    let factors = [("cols", range(size.clone())), ("rows", range(size))];
+    map.insert(
+        Value::String("factors".into()),
+        Value::Map(Map::from_iter(
+            factors.map(|(k, v)| (Value::String(k.into()), v)),
+        )),
+    );
+    Ok(Value::Map(map))
+}
+
+fn add_division(value: Value) -> Result<Value, Error> {
+    add_empty_grid(value, "division")
+}
+
+fn add_addition_and_subtraction(value: Value) -> Result<Value, Error> {
+    add_empty_grid(add_empty_grid(value, "addition")?, "subtraction")
+}
+
+fn add_commutative(value: Value) -> Result<Value, Error> {
+    let Value::Map(mut map) = value else {
+        bail!("profile isn't a struct");
+    };
+    map.insert(Value::String("commutative".into()), Value::Bool(false));
+    Ok(Value::Map(map))
+}
+
+fn add_fluency_threshold(value: Value) -> Result<Value, Error> {
+    let Value::Map(mut map) = value else {
+        bail!("profile isn't a struct");
+    };
+    map.insert(
+        Value::String("fluency_ms".into()),
+        Value::Number(Number::from(DEFAULT_FLUENCY_MS as u64)),
+    );
+    Ok(Value::Map(map))
+}
+
+/// Starts the `field` grid with the shape of the multiplication one and no answers
+fn add_empty_grid(value: Value, field: &str) -> Result<Value, Error> {
+    let Value::Map(mut map) = value else {
+        bail!("profile isn't a struct");
+    };
+    let empty_score = || {
+        let no_tries = || Value::Number(Number::from(0));
+        Value::Seq(vec![no_tries(), no_tries(), Value::Seq(vec![])])
+    };
+    let grid = map
+        .iter()
+        .find_map(|(key, value)| match (key, value) {
+            (Value::String(key), Value::Seq(rows)) if key == "scores" => Some(
+                rows.iter()
+                    .map(|row| match row {
+                        Value::Seq(row) => Value::Seq(row.iter().map(|_| empty_score()).collect()),
+                        _ => Value::Seq(vec![]),
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("profile has no scores"))?;
+    map.insert(Value::String(field.into()), Value::Seq(grid));
+    Ok(Value::Map(map))
+}
+
 #[derive(PartialEq, Eq)]
 pub struct ScoreWithEq(usize, usize, Arc<Score>);
 
+impl ScoreWithEq {
+    /// Both numbers of the equation, starting from 1
+    pub fn factors(&self) -> (usize, usize) {
+        (self.0, self.1)
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.2
+    }
+}
+
 impl PartialOrd for ScoreWithEq {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -142,8 +457,11 @@ impl From<ScoreWithEq> for (u32, u32) {
 }
 
 pub struct ScoresIter<'user> {
+    /// next factors, starting from 1
     index_x: usize,
     index_y: usize,
+    factors: Factors,
+    operation: Operation,
     user: &'user User,
 }
 
@@ -151,17 +469,165 @@ impl<'u> Iterator for ScoresIter<'u> {
     type Item = ScoreWithEq;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let row = self.user.scores.get(self.index_x)?;
-        let val = row.get(self.index_y)?;
-        let v = ScoreWithEq(self.index_x + 1, self.index_y + 1, Arc::clone(val));
-        if self.index_y == CELL_N - 1 {
+        loop {
+            let (a, b) = (self.index_x, self.index_y);
+            if !self.factors.contains(a, b) {
+                return None;
+            }
+            self.advance();
+            // a shared fact is drawn once, from the cell with the smaller first factor
+            if a > b && self.user.merges_mirrored(self.operation) && self.factors.contains(b, a) {
+                continue;
+            }
+            let val = self.user.get_opt_score(self.operation, a - 1, b - 1)?;
+            return Some(ScoreWithEq(a, b, val));
+        }
+    }
+}
+
+impl<'u> ScoresIter<'u> {
+    fn advance(&mut self) {
+        if self.index_y == *self.factors.rows().end() {
             self.index_x += 1;
-            self.index_y = 0;
+            self.index_y = *self.factors.rows().start();
         } else {
             self.index_y += 1;
         }
-        Some(v)
     }
 }
 
 pub(crate) mod score {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        schedule::Review,
+        score::Attempt,
+        versioned::{from_versioned_bytes, to_versioned_string},
+    };
+
+    const GRID: &str = "[[(1,1),(0,0),(0,0)],[(0,0),(3,2),(0,0)],[(0,0),(0,0),(4,0)]]";
+
+    fn field<'v>(value: &'v Value, name: &str) -> Option<&'v Value> {
+        let Value::Map(map) = value else {
+            return None;
+        };
+        map.iter()
+            .find_map(|(k, v)| matches!(k, Value::String(k) if k == name).then_some(v))
+    }
+
+    #[test]
+    fn size_comes_from_the_grid() {
+        let profile: Value = ron::from_str(&format!("(name:\"A\",scores:{GRID})")).unwrap();
+        let migrated = add_size(profile).unwrap();
+        assert_eq!(field(&migrated, "size"), Some(&Value::Number(3.into())));
+        assert!(add_size(Value::Unit).is_err());
+    }
+
+    #[test]
+    fn size_becomes_a_square_range() {
+        let profile: Value = ron::from_str(&format!("(name:\"A\",size:3,scores:{GRID})")).unwrap();
+        let migrated = size_to_factors(profile).unwrap();
+        assert_eq!(field(&migrated, "size"), None);
+        let factors: Factors = field(&migrated, "factors")
+            .unwrap()
+            .clone()
+            .into_rust()
+            .unwrap();
+        assert_eq!(factors, Factors::square(3));
+    }
+
+    #[test]
+    fn empty_grid_has_the_shape_of_the_scores() {
+        let profile: Value = ron::from_str(&format!("(name:\"A\",scores:{GRID})")).unwrap();
+        let migrated = add_empty_grid(profile, "division").unwrap();
+        let grid: Vec<Vec<Score>> = field(&migrated, "division")
+            .unwrap()
+            .clone()
+            .into_rust()
+            .unwrap();
+        assert_eq!(grid.len(), 3);
+        assert!(grid.iter().all(|row| row.len() == 3));
+        assert!(grid.iter().flatten().all(|s| s.tries == 0));
+    }
+
+    #[test]
+    fn oldest_profiles_keep_their_scores() {
+        let user: User =
+            from_versioned_bytes(format!("(name:\"A\",scores:{GRID})").as_bytes()).unwrap();
+        assert_eq!(user.factors(), &Factors::square(3));
+        let counts = |operation, x, y| {
+            let s = user.get_opt_score(operation, x, y).unwrap();
+            (s.tries, s.correct)
+        };
+        assert_eq!(counts(Operation::Multiplication, 0, 0), (1, 1));
+        assert_eq!(counts(Operation::Multiplication, 1, 1), (3, 2));
+        assert_eq!(counts(Operation::Multiplication, 2, 2), (4, 0));
+        for operation in [
+            Operation::Division,
+            Operation::Addition,
+            Operation::Subtraction,
+        ] {
+            assert_eq!(user.grid(operation).len(), 3);
+            assert_eq!(counts(operation, 2, 2), (0, 0));
+        }
+        assert!(!user.is_commutative());
+        assert_eq!(
+            user.fluency_threshold(),
+            Duration::from_millis(DEFAULT_FLUENCY_MS as u64)
+        );
+    }
+
+    #[test]
+    fn answer_log_survives_the_migrations() {
+        let scores = "[[(0,0,[(at:5,value:Some(1),correct:true,time_ms:Some(900))]),(0,0)],\
+                      [(0,0),(2,1)]]";
+        let profile = format!(
+            "(version:3,data:(name:\"A\",factors:(cols:(start:1,end:2),rows:(start:1,end:2)),\
+             scores:{scores}))"
+        );
+        let user: User = from_versioned_bytes(profile.as_bytes()).unwrap();
+        let score = user.get_opt_score(Operation::Multiplication, 0, 0).unwrap();
+        assert_eq!(score.history().len(), 1);
+        assert_eq!(score.history()[0].value(), Some(1));
+        assert!(score.review().is_some());
+
+        let saved = to_versioned_string(&user).unwrap();
+        let reloaded: User = from_versioned_bytes(saved.as_bytes()).unwrap();
+        assert_eq!(to_versioned_string(&reloaded).unwrap(), saved);
+    }
+
+    #[test]
+    fn replays_missing_schedules_with_the_profile_threshold() {
+        let logged = "[[(0,0,[(at:5,value:Some(1),correct:true,time_ms:Some(4000))])]]";
+        let profile = format!(
+            "(version:7,data:(name:\"A\",factors:(cols:(start:1,end:1),rows:(start:1,end:1)),\
+             scores:{logged},division:[[(0,0)]],addition:[[(0,0)]],subtraction:[[(0,0)]],\
+             commutative:false,fluency_ms:5000))"
+        );
+        let user: User = from_versioned_bytes(profile.as_bytes()).unwrap();
+        let score = user.get_opt_score(Operation::Multiplication, 0, 0).unwrap();
+        let fast = Review::replay(score.history(), Duration::from_secs(5));
+        let slow = Review::replay(score.history(), Duration::from_secs(3));
+        assert_eq!(score.review(), fast.as_ref());
+        assert_ne!(fast, slow);
+    }
+
+    #[test]
+    fn records_into_scores_shared_with_a_copy() {
+        let mut user = User::new("A");
+        let saved = user.clone();
+        let fluency = user.fluency_threshold();
+        let score = user.get_mut_score(Operation::Multiplication, 2, 3).unwrap();
+        score.record(Attempt::new(Some(12), true, None), fluency);
+
+        let recorded = |u: &User| {
+            u.get_opt_score(Operation::Multiplication, 2, 3)
+                .unwrap()
+                .tries
+        };
+        assert_eq!(recorded(&user), 1);
+        assert_eq!(recorded(&saved), 0);
+    }
+}
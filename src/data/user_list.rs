@@ -1,16 +1,22 @@
-use std::{path::Path, sync::Arc};
+use std::sync::Arc;
 
-use crate::helpers::{get_file_path, load_file};
-use anyhow::{anyhow, Error};
+use anyhow::{bail, Error};
 use serde::{Deserialize, Serialize};
-use tokio::io::AsyncWriteExt;
 
-use super::consts::create_app_dir;
+use super::{
+    store::{run, Store},
+    versioned::{unchanged, Migration, Versioned},
+};
+
+/// Name the list itself is stored under, so no user can have it
+pub const USER_LIST_FILE: &str = "UserList";
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UserList {
     last_user: String,
     all_users: Vec<String>,
+    #[serde(default)]
+    archived: Vec<String>,
 }
 
 impl Default for UserList {
@@ -18,37 +24,43 @@ impl Default for UserList {
         Self {
             last_user: String::from("User"),
             all_users: vec![String::from("User")],
+            archived: vec![],
         }
     }
 }
 
 impl UserList {
-    fn get_file_path() -> std::path::PathBuf {
-        get_file_path("UserList")
-    }
-
-    async fn load_from_file_anyhow(path: impl AsRef<Path>) -> Result<Self, Error> {
-        create_app_dir().await;
-        Ok(ron::de::from_bytes(&load_file(path).await?)?)
-    }
-    pub async fn load_from_file() -> Self {
-        if let Ok(list) = Self::load_from_file_anyhow(Self::get_file_path()).await {
-            list
-        } else {
-            let new_list = Self::default();
-            let mut file = tokio::fs::File::create(Self::get_file_path())
-                .await
-                .unwrap();
-
-            file.write_all(ron::ser::to_string(&new_list).unwrap().as_bytes())
-                .await
-                .unwrap();
-            new_list
+    /// Loads the list, or builds a new one from the profiles found in the store.
+    /// The returned error describes why an existing list couldn't be used
+    pub async fn load_from_file(store: Store) -> (Self, Option<Arc<Error>>) {
+        let error = match run(&store, |s| s.load_user_list()).await {
+            Ok(Some(list)) => return (list, None),
+            Ok(None) => None,
+            Err(e) => Some(Arc::new(e)),
+        };
+        let profiles = run(&store, |s| s.list_profiles()).await.unwrap_or_default();
+        let archived = run(&store, |s| s.list_archived_profiles())
+            .await
+            .unwrap_or_default();
+        let new_list = Self::from_profiles(profiles, archived);
+        if let Err(e) = new_list.save_to_file(store).await {
+            return (new_list, error.or(Some(e)));
         }
+        (new_list, error)
     }
 
-    pub async fn create_new(path: impl AsRef<Path>) -> Result<Self, Arc<Error>> {
-        Self::load_from_file_anyhow(path).await.map_err(Arc::new)
+    pub(crate) fn from_profiles(all_users: Vec<String>, archived: Vec<String>) -> Self {
+        match all_users.first() {
+            Some(first) => Self {
+                last_user: first.clone(),
+                all_users,
+                archived,
+            },
+            None => Self {
+                archived,
+                ..Self::default()
+            },
+        }
     }
 
     pub fn get_current(&self) -> &str {
@@ -59,37 +71,139 @@ impl UserList {
         &self.all_users
     }
 
+    pub fn get_archived(&self) -> &Vec<String> {
+        &self.archived
+    }
+
+    /// Whether the name is taken by an active or an archived user
+    pub fn contains(&self, user_name: &str) -> bool {
+        self.all_users
+            .iter()
+            .chain(&self.archived)
+            .any(|u| u == user_name)
+    }
+
     pub fn add_user(&mut self, user_name: &str) {
         self.all_users.push(user_name.to_owned());
         self.last_user = user_name.to_owned();
     }
 
+    /// Adds a user without making it the current one
+    pub fn insert_user(&mut self, user_name: &str) {
+        self.all_users.push(user_name.to_owned());
+    }
+
     pub fn switch_current(&mut self, new_current: String) {
         self.last_user = new_current;
     }
 
-    pub fn update_current_user_after_rename(&mut self, user_name: String) {
-        let _ = std::mem::replace(
-            self.all_users
-                .iter_mut()
-                .find(|u| *u == &self.last_user)
-                .unwrap(),
-            user_name.clone(),
-        );
-        self.switch_current(user_name);
+    /// Checks that `user_name` can be used as a name at all, whether it's taken or not.
+    /// Profiles may be stored in files named after their users, so the name must be usable
+    /// as one: no path separators or `..`, no leading dot of hidden files, and not the list's own name
+    pub fn check_name(user_name: &str) -> Result<(), Error> {
+        if user_name.trim().is_empty()
+            || user_name.contains(['/', '\\'])
+            || user_name.contains("..")
+            || user_name.starts_with('.')
+            || user_name.eq_ignore_ascii_case(USER_LIST_FILE)
+        {
+            bail!("\"{user_name}\" can't be used as a user name");
+        }
+        Ok(())
     }
 
-    pub async fn save_to_file(&self) -> Result<(), Arc<Error>> {
-        let mut file = tokio::fs::File::options()
-            .write(true)
-            .truncate(true)
-            .open(Self::get_file_path())
-            .await
-            .map_err(|e| Arc::new(anyhow!(e)))?;
-        file.write_all(ron::ser::to_string(self).unwrap().as_bytes())
+    /// Checks that `user_name` can be given to a new or renamed user
+    pub fn check_new_name(&self, user_name: &str) -> Result<(), Error> {
+        Self::check_name(user_name)?;
+        if self.contains(user_name) {
+            bail!("user {user_name} already exists");
+        }
+        Ok(())
+    }
+
+    pub fn rename_user(&mut self, old_name: &str, new_name: &str) -> Result<(), Error> {
+        self.check_new_name(new_name)?;
+        let Some(entry) = self.all_users.iter_mut().find(|u| *u == old_name) else {
+            bail!("{old_name} isn't an active user");
+        };
+        *entry = new_name.to_owned();
+        if self.last_user == old_name {
+            self.last_user = new_name.to_owned();
+        }
+        Ok(())
+    }
+
+    /// Moves an active user to the archive, switching the current user if needed
+    pub fn archive_user(&mut self, user_name: &str) -> Result<(), Error> {
+        let user = self.remove_active(user_name)?;
+        self.archived.push(user);
+        Ok(())
+    }
+
+    pub fn restore_user(&mut self, user_name: &str) -> Result<(), Error> {
+        let Some(i) = self.archived.iter().position(|u| u == user_name) else {
+            bail!("{user_name} isn't archived");
+        };
+        let user = self.archived.remove(i);
+        self.all_users.push(user);
+        Ok(())
+    }
+
+    /// Forgets an active or archived user, switching the current user if needed
+    pub fn delete_user(&mut self, user_name: &str) -> Result<(), Error> {
+        if let Some(i) = self.archived.iter().position(|u| u == user_name) {
+            self.archived.remove(i);
+        } else {
+            self.remove_active(user_name)?;
+        }
+        Ok(())
+    }
+
+    fn remove_active(&mut self, user_name: &str) -> Result<String, Error> {
+        let Some(i) = self.all_users.iter().position(|u| u == user_name) else {
+            bail!("{user_name} isn't an active user");
+        };
+        if self.all_users.len() == 1 {
+            bail!("{user_name} is the only active user");
+        }
+        let user = self.all_users.remove(i);
+        if self.last_user == user {
+            self.last_user = self.all_users[0].clone();
+        }
+        Ok(user)
+    }
+
+    pub async fn save_to_file(&self, store: Store) -> Result<(), Arc<Error>> {
+        let to_save = self.clone();
+        run(&store, move |s| s.save_user_list(&to_save))
             .await
-            .map_err(|e| Arc::new(anyhow!(e)))?;
+            .map_err(Arc::new)?;
         println!("saved user list successfully \n {self:#?}");
         Ok(())
     }
 }
+
+impl Versioned for UserList {
+    const MIGRATIONS: &'static [Migration] = &[
+        // 0 -> 1: list wrapped in a versioned envelope
+        unchanged,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_names_that_can_not_be_file_names() {
+        let mut list = UserList::default();
+        list.insert_user("Ann");
+        for name in [
+            "", "  ", "a/b", "a\\b", "../up", "..", ".hidden", "UserList", "userlist",
+        ] {
+            assert!(list.check_new_name(name).is_err(), "{name:?}");
+        }
+        assert!(list.check_new_name("Ann").is_err());
+        assert!(list.check_new_name("Bob").is_ok());
+    }
+}
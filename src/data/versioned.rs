@@ -0,0 +1,137 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Error, Result};
+use ron::Value;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub(crate) const QUARANTINE_DIR: &str = "quarantine";
+
+/// Turns the data of one file version into the next one
+pub(crate) type Migration = fn(Value) -> Result<Value>;
+
+/// Layout of a file the app persists, together with the chain of migrations from older layouts.
+///
+/// Version 0 is the bare layout written before files had an envelope,
+/// `MIGRATIONS[i]` converts version `i` into version `i + 1`.
+pub(crate) trait Versioned: Serialize + DeserializeOwned {
+    const MIGRATIONS: &'static [Migration];
+
+    fn current_version() -> u32 {
+        Self::MIGRATIONS.len() as u32
+    }
+
+    /// Completes data read from any version once the migrations ran,
+    /// for what can't be worked out from the layout alone
+    fn loaded(self) -> Self {
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct RawEnvelope {
+    version: u32,
+    data: Value,
+}
+
+/// File written by a newer version of the app. It's valid, so unlike a corrupt one it's left in place
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewerVersion {
+    pub version: u32,
+    pub supported: u32,
+}
+
+impl std::fmt::Display for NewerVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "file version {} is newer than this app supports ({}), update the app to open it",
+            self.version, self.supported
+        )
+    }
+}
+
+impl std::error::Error for NewerVersion {}
+
+/// Migration for layouts that didn't change between versions
+pub(crate) fn unchanged(value: Value) -> Result<Value> {
+    Ok(value)
+}
+
+pub(crate) fn to_versioned_string<T: Versioned>(data: &T) -> Result<String> {
+    Ok(ron::ser::to_string(&Envelope {
+        version: T::current_version(),
+        data,
+    })?)
+}
+
+pub(crate) fn from_versioned_bytes<T: Versioned>(bytes: &[u8]) -> Result<T> {
+    let (version, data) = match ron::de::from_bytes::<RawEnvelope>(bytes) {
+        Ok(envelope) => (envelope.version, envelope.data),
+        Err(_) => (0, ron::de::from_bytes::<Value>(bytes)?),
+    };
+    migrate(version, data)
+}
+
+/// Envelope of `data` as a value, for layouts that embed other versioned data
+pub(crate) fn to_versioned_value<T: Versioned>(data: &T) -> Result<Value> {
+    Ok(ron::from_str(&to_versioned_string(data)?)?)
+}
+
+/// Reads data embedded with [`to_versioned_value`], a value without an envelope is version 0
+pub(crate) fn from_versioned_value<T: Versioned>(value: Value) -> Result<T> {
+    match value.clone().into_rust::<RawEnvelope>() {
+        Ok(envelope) => migrate(envelope.version, envelope.data),
+        Err(_) => migrate(0, value),
+    }
+}
+
+fn migrate<T: Versioned>(version: u32, mut data: Value) -> Result<T> {
+    let migrations = T::MIGRATIONS.get(version as usize..).ok_or(NewerVersion {
+        version,
+        supported: T::current_version(),
+    })?;
+    for migrate in migrations {
+        data = migrate(data)?;
+    }
+    Ok(T::loaded(data.into_rust()?))
+}
+
+/// Moves an unreadable file into `root`'s quarantine directory, so that it is never overwritten
+pub(crate) fn quarantine(root: &Path, path: &Path, cause: Error) -> Error {
+    match move_to_quarantine(root, path) {
+        Ok(to) => cause.context(format!(
+            "{} couldn't be read and was moved to {}",
+            path.display(),
+            to.display()
+        )),
+        Err(e) => cause.context(format!(
+            "{} couldn't be read and couldn't be moved to quarantine ({e})",
+            path.display()
+        )),
+    }
+}
+
+fn move_to_quarantine(root: &Path, path: &Path) -> Result<PathBuf> {
+    let mut to = root.join(QUARANTINE_DIR);
+    std::fs::create_dir_all(&to)?;
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    to.push(format!("{stem}-{secs}.ron"));
+    std::fs::rename(path, &to)?;
+    Ok(to)
+}
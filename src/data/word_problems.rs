@@ -0,0 +1,316 @@
+use std::{collections::HashMap, io::ErrorKind, path::Path, sync::Arc};
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use super::operation::Operation;
+
+/// Templates shipped with the app, files in the templates directory are added to them
+const BUILT_IN: [&str; 2] = [
+    include_str!("../../templates/en.ron"),
+    include_str!("../../templates/pl.ron"),
+];
+
+#[derive(Deserialize, Debug, Clone)]
+struct Template {
+    operation: Operation,
+    text: String,
+}
+
+/// Contents of a single template file, see `templates/en.ron` for the format
+#[derive(Deserialize, Debug, Clone)]
+struct TemplateFile {
+    language: String,
+    /// forms of every noun, in the order of the language's plural rule
+    #[serde(default)]
+    nouns: HashMap<String, Vec<String>>,
+    templates: Vec<Template>,
+}
+
+impl TemplateFile {
+    /// Fills in the numbers and noun forms of `text` for the fact `(a, b)`
+    fn render(&self, text: &str, a: u32, b: u32) -> Result<String> {
+        let mut rendered = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| anyhow!("unclosed {{ in \"{text}\""))?
+                + start;
+            let placeholder = &rest[start + 1..end];
+            match placeholder.split_once(':') {
+                Some((noun, number)) => rendered.push_str(self.noun(noun, value(number, a, b)?)?),
+                None => rendered.push_str(&value(placeholder, a, b)?.to_string()),
+            }
+            rest = &rest[end + 1..];
+        }
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
+
+    /// Form of `noun` that goes with the number `n`
+    fn noun(&self, noun: &str, n: u32) -> Result<&str> {
+        let forms = self
+            .nouns
+            .get(noun)
+            .filter(|forms| !forms.is_empty())
+            .ok_or_else(|| anyhow!("no noun \"{noun}\" in \"{}\"", self.language))?;
+        // languages with more forms can list just two, the plural one stands for all of them
+        let form = plural_form(&self.language, n).min(forms.len() - 1);
+        Ok(&forms[form])
+    }
+
+    /// Adds the nouns and templates of `other`, its nouns replace the ones already defined
+    fn extend(&mut self, other: TemplateFile) {
+        self.nouns.extend(other.nouns);
+        self.templates.extend(other.templates);
+    }
+}
+
+/// Number a placeholder stands for
+fn value(placeholder: &str, a: u32, b: u32) -> Result<u32> {
+    match placeholder {
+        "a" => Ok(a),
+        "b" => Ok(b),
+        "sum" => Ok(a + b),
+        "product" => Ok(a * b),
+        _ => bail!("unknown placeholder {{{placeholder}}}"),
+    }
+}
+
+/// Index of the noun form used with `n`: one, then few and many for Polish, one and other for the rest
+fn plural_form(language: &str, n: u32) -> usize {
+    match language {
+        "pl" if n == 1 => 0,
+        "pl" if (2..=4).contains(&(n % 10)) && !(12..=14).contains(&(n % 100)) => 1,
+        "pl" => 2,
+        _ => usize::from(n != 1),
+    }
+}
+
+/// Question built from a template for one fact of the table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordProblem {
+    text: String,
+    operation: Operation,
+    numbers: (u32, u32),
+}
+
+impl WordProblem {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// The fact `(a, b)` the problem asks about
+    pub fn numbers(&self) -> (u32, u32) {
+        self.numbers
+    }
+}
+
+/// Word problem templates of every language, one entry per language
+#[derive(Debug, Clone)]
+pub struct Library {
+    languages: Vec<TemplateFile>,
+}
+
+impl Default for Library {
+    fn default() -> Self {
+        let mut library = Self { languages: vec![] };
+        for file in BUILT_IN {
+            // SAFETY - the built-in templates are checked when they change
+            library.add(ron::from_str(file).unwrap());
+        }
+        library
+    }
+}
+
+impl Library {
+    /// Built-in templates plus every `.ron` file in `dir`.
+    /// Broken files are skipped, the returned error names the first of them
+    pub fn load(dir: &Path) -> (Self, Option<Arc<Error>>) {
+        let mut library = Self::default();
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return (library, None),
+            Err(e) => return (library, Some(Arc::new(e.into()))),
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|e| Some(e.ok()?.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+            .collect();
+        paths.sort();
+        let mut error = None;
+        for path in paths {
+            let added = std::fs::read_to_string(&path)
+                .map_err(Error::from)
+                .and_then(|s| Ok(ron::from_str(&s)?))
+                .and_then(|file| library.add_checked(file))
+                .with_context(|| format!("couldn't load templates from {}", path.display()));
+            if let Err(e) = added {
+                error.get_or_insert(Arc::new(e));
+            }
+        }
+        (library, error)
+    }
+
+    /// Adds a file of the templates directory, unless one of its templates can't be filled in
+    fn add_checked(&mut self, file: TemplateFile) -> Result<()> {
+        // nouns may come from other files of the same language
+        let mut nouns = self
+            .languages
+            .iter()
+            .find(|l| l.language == file.language)
+            .map(|l| l.nouns.clone())
+            .unwrap_or_default();
+        nouns.extend(file.nouns.clone());
+        let merged = TemplateFile {
+            nouns,
+            templates: vec![],
+            ..file.clone()
+        };
+        for template in &file.templates {
+            merged.render(&template.text, 2, 5)?;
+        }
+        self.add(file);
+        Ok(())
+    }
+
+    fn add(&mut self, file: TemplateFile) {
+        match self
+            .languages
+            .iter_mut()
+            .find(|l| l.language == file.language)
+        {
+            Some(language) => language.extend(file),
+            None => self.languages.push(file),
+        }
+    }
+
+    pub fn languages(&self) -> Vec<&str> {
+        self.languages.iter().map(|l| l.language.as_str()).collect()
+    }
+
+    /// A problem for every fact of `operation`, each from a random template of `language`
+    pub fn generate(
+        &self,
+        language: &str,
+        operation: Operation,
+        facts: &[(u32, u32)],
+        rng: &mut impl Rng,
+    ) -> Result<Vec<WordProblem>> {
+        let file = self
+            .languages
+            .iter()
+            .find(|l| l.language == language)
+            .ok_or_else(|| anyhow!("no word problems in \"{language}\""))?;
+        let templates: Vec<_> = file
+            .templates
+            .iter()
+            .filter(|t| t.operation == operation)
+            .collect();
+        if templates.is_empty() {
+            bail!(
+                "no {} word problems in \"{language}\"",
+                operation.label().to_lowercase()
+            );
+        }
+        facts
+            .iter()
+            .map(|&(a, b)| {
+                // SAFETY - checked to be non-empty above
+                let template = templates.choose(rng).unwrap();
+                Ok(WordProblem {
+                    text: file.render(&template.text, a, b)?,
+                    operation,
+                    numbers: (a, b),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(language: &str, forms: &[&str]) -> TemplateFile {
+        TemplateFile {
+            language: language.into(),
+            nouns: HashMap::from([(
+                "apple".into(),
+                forms.iter().map(|f| f.to_string()).collect(),
+            )]),
+            templates: vec![],
+        }
+    }
+
+    #[test]
+    fn picks_polish_forms() {
+        for (n, form) in [
+            (1, 0),
+            (2, 1),
+            (4, 1),
+            (5, 2),
+            (11, 2),
+            (12, 2),
+            (14, 2),
+            (21, 2),
+            (22, 1),
+            (104, 1),
+            (112, 2),
+            (0, 2),
+        ] {
+            assert_eq!(plural_form("pl", n), form, "{n}");
+        }
+    }
+
+    #[test]
+    fn picks_one_or_other_for_the_rest() {
+        for (n, form) in [(0, 1), (1, 0), (2, 1), (21, 1)] {
+            assert_eq!(plural_form("en", n), form, "{n}");
+        }
+    }
+
+    #[test]
+    fn plural_form_stands_for_missing_ones() {
+        let pl = file("pl", &["jabłko", "jabłka"]);
+        assert_eq!(pl.noun("apple", 5).unwrap(), "jabłka");
+        let pl = file("pl", &["jabłko", "jabłka", "jabłek"]);
+        assert_eq!(pl.noun("apple", 5).unwrap(), "jabłek");
+        assert!(pl.noun("pear", 5).is_err());
+    }
+
+    #[test]
+    fn renders_numbers_and_nouns() {
+        let en = file("en", &["apple", "apples"]);
+        assert_eq!(
+            en.render(
+                "{a} boxes of {b} {apple:b}, {product} {apple:product}",
+                3,
+                1
+            )
+            .unwrap(),
+            "3 boxes of 1 apple, 3 apples"
+        );
+        assert!(en.render("{a} {c}", 3, 1).is_err());
+        assert!(en.render("{a", 3, 1).is_err());
+    }
+
+    #[test]
+    fn built_in_templates_render() {
+        for file in BUILT_IN {
+            let file: TemplateFile = ron::from_str(file).unwrap();
+            for template in &file.templates {
+                for (a, b) in [(1, 1), (2, 5), (12, 22)] {
+                    file.render(&template.text, a, b).unwrap();
+                }
+            }
+        }
+    }
+}
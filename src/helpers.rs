@@ -1,13 +1,12 @@
-use anyhow::Result;
 use iced::{
     widget::{text, Column, Row, Text},
     Element,
 };
-use std::{iter::Take, path::Path, vec::IntoIter};
+use std::{iter::Take, vec::IntoIter};
 
 use crate::{
     components::equation::EqData,
-    data::{consts::CELL_N, user::ScoreWithEq},
+    data::{operation::Operation, user::ScoreWithEq},
 };
 
 pub fn centered_text<'a, Renderer: iced::advanced::text::Renderer>(
@@ -37,34 +36,23 @@ pub fn extend_row<'a, Message, Renderer>(
 
 mod cell {}
 
-pub fn get_n(scores: &mut Take<IntoIter<ScoreWithEq>>) -> Option<[EqData; CELL_N]> {
-    let v: Vec<(u32, u32)> = scores.take(CELL_N).map(|s| s.into()).collect();
-    if v.len() == CELL_N {
-        Some(
-            v.into_iter()
-                .map(|e| EqData::new(Some(e)))
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap(),
-        )
-    } else {
-        None
-    }
-}
-
-pub fn make_nxn_mat<T: Default + std::fmt::Debug>() -> [[T; CELL_N]; CELL_N] {
-    (0..CELL_N)
-        .map(|_| {
-            TryInto::<[T; CELL_N]>::try_into((0..CELL_N).map(|_| T::default()).collect::<Vec<_>>())
-                .unwrap()
-        })
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap()
+/// Takes up to `n` equations, `None` once there are none left
+pub fn get_n(
+    scores: &mut Take<IntoIter<ScoreWithEq>>,
+    n: usize,
+    operation: Operation,
+) -> Option<Vec<EqData>> {
+    let v: Vec<EqData> = scores
+        .take(n)
+        .map(|s| EqData::new(Some(s.into())).operation(operation))
+        .collect();
+    (!v.is_empty()).then_some(v)
 }
 
-pub async fn load_file(path: impl AsRef<Path>) -> Result<Vec<u8>> {
-    Ok(tokio::fs::read(path).await?)
+pub fn make_nxn_mat<T: Default>(n: usize) -> Vec<Vec<T>> {
+    (0..n)
+        .map(|_| (0..n).map(|_| T::default()).collect())
+        .collect()
 }
 
 pub fn convert_to_msg<T, E, Message>(
@@ -76,9 +64,3 @@ pub fn convert_to_msg<T, E, Message>(
         Err(e) => on_error(e),
     }
 }
-
-pub fn get_file_path(name: &str) -> std::path::PathBuf {
-    let mut path = crate::data::consts::app_dir();
-    path.push(format!("{name}.ron"));
-    path
-}
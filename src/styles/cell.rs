@@ -47,6 +47,7 @@ pub enum CellColor {
     Grey,
     DarkGrey,
     Green,
+    LightGreen,
     Orange,
     Yellow,
     Red,
@@ -61,7 +62,14 @@ impl From<Score> for CellColor {
 
 impl From<&Score> for CellColor {
     fn from(s: &Score) -> Self {
-        match s.get_percentage() {
+        Self::of_percentage(s.get_percentage())
+    }
+}
+
+impl CellColor {
+    /// Colour of a share in the units of [`MAX_PERCENT`], from red to green, white when there's none
+    pub fn of_percentage(percentage: Option<u16>) -> Self {
+        match percentage {
             Some(p) if p < MAX_PERCENT / 10 * 2 => Self::Red,
             Some(p) if p < MAX_PERCENT / 10 * 5 => Self::Orange,
             Some(p) if p < MAX_PERCENT / 10 * 7 => Self::Yellow,
@@ -69,6 +77,17 @@ impl From<&Score> for CellColor {
             Some(_) | None => Self::White,
         }
     }
+
+    /// Colour of the Leitner box `n`, from red for the first one to green for the last
+    pub fn of_box(n: usize) -> Self {
+        match n {
+            1 => Self::Red,
+            2 => Self::Orange,
+            3 => Self::Yellow,
+            4 => Self::LightGreen,
+            _ => Self::Green,
+        }
+    }
 }
 
 impl From<&CellColor> for iced::Background {
@@ -96,6 +115,7 @@ impl From<&CellColor> for iced::Color {
             CellColor::Grey => color!(176, 176, 176),
             CellColor::White => color!(255, 255, 255),
             CellColor::Green => color!(59, 191, 89),
+            CellColor::LightGreen => color!(163, 219, 110),
             CellColor::Orange => color!(245, 135, 32),
             CellColor::Yellow => color!(252, 223, 3),
             CellColor::DarkGrey => color!(119, 147, 158),